@@ -0,0 +1,626 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    http::{self, header, HeaderMap, HeaderName},
+};
+use http_body::Frame;
+use opentelemetry::KeyValue;
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::{poll_fn, Future},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+/// The default maximum body size captured: 4 KiB.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 4096;
+
+/// A header which can be set with [`BodyCaptureLayer::debug_header`] to enable capturing for
+/// successful responses.
+pub const X_DEBUG_BODY: HeaderName = HeaderName::from_static("x-debug-body");
+
+const REDACTED: &str = "[REDACTED]";
+const SENSITIVE_KEYS: [&str; 6] = [
+    "authorization",
+    "password",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+];
+
+type Redact = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// A [`Layer`] which captures request and response bodies as span events for debugging.
+///
+/// Bodies are attached to the current span as `http.request.body` and `http.response.body`
+/// events when the response is a client or server error, or when the request carries the
+/// [debug header](Self::debug_header), which is not set by default. Only bodies whose content type is allowed and whose size is known to be at
+/// most [`max_body_size`](Self::max_body_size) bytes are buffered, streaming bodies are passed
+/// through untouched. Every payload goes through the redaction step before it is attached.
+///
+/// A body which fails while it is buffered is not captured. The bytes read so far and the
+/// error are passed on unchanged to the handler or the client, and a
+/// `http.request.body.capture_failed` or `http.response.body.capture_failed` event with the
+/// error is attached instead.
+///
+/// It must be added inside the `TraceLayer`, so that the events end up on the request span.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{routing::post, Router};
+/// use axum_otel::{AxumOtelSpanCreator, BodyCaptureLayer};
+/// use tower::ServiceBuilder;
+/// use tower_http::trace::TraceLayer;
+///
+/// async fn handler(body: String) -> String {
+///     body
+/// }
+///
+/// let app: Router<()> = Router::new().route("/", post(handler)).layer(
+///     ServiceBuilder::new()
+///         .layer(TraceLayer::new_for_http().make_span_with(AxumOtelSpanCreator::new()))
+///         .layer(
+///             BodyCaptureLayer::new()
+///                 .max_body_size(1024)
+///                 .redact(|body: &str| body.replace("secret", "******")),
+///         ),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct BodyCaptureLayer {
+    config: Arc<BodyCaptureConfig>,
+}
+
+#[derive(Clone)]
+struct BodyCaptureConfig {
+    max_body_size: usize,
+    content_types: Vec<String>,
+    debug_header: Option<HeaderName>,
+    redact: Redact,
+}
+
+impl Default for BodyCaptureLayer {
+    fn default() -> Self {
+        Self {
+            config: Arc::new(BodyCaptureConfig {
+                max_body_size: DEFAULT_MAX_BODY_SIZE,
+                content_types: vec![
+                    "application/json".to_string(),
+                    "application/x-www-form-urlencoded".to_string(),
+                ],
+                debug_header: None,
+                redact: Arc::new(redact_sensitive_fields),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for BodyCaptureLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyCaptureLayer")
+            .field("max_body_size", &self.config.max_body_size)
+            .field("content_types", &self.config.content_types)
+            .field("debug_header", &self.config.debug_header)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BodyCaptureLayer {
+    /// Create a new `BodyCaptureLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum size in bytes of a captured body.
+    ///
+    /// Defaults to [`DEFAULT_MAX_BODY_SIZE`].
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        Arc::make_mut(&mut self.config).max_body_size = max_body_size;
+        self
+    }
+
+    /// Set the content types whose bodies are captured, parameters such as `charset` are ignored.
+    ///
+    /// Defaults to `application/json` and `application/x-www-form-urlencoded`.
+    pub fn content_types<I, T>(mut self, content_types: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Arc::make_mut(&mut self.config).content_types = content_types
+            .into_iter()
+            .map(|content_type| content_type.into().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Set a header, such as [`X_DEBUG_BODY`], which enables capturing for responses that are
+    /// not errors.
+    ///
+    /// Any client can send it, so only set it where clients are trusted, or strip it from
+    /// untrusted requests in front of this layer. Not set by default.
+    pub fn debug_header(mut self, debug_header: HeaderName) -> Self {
+        Arc::make_mut(&mut self.config).debug_header = Some(debug_header);
+        self
+    }
+
+    /// Set the redaction step applied to every captured body.
+    ///
+    /// Defaults to [`redact_sensitive_fields`].
+    pub fn redact<F>(mut self, redact: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.config).redact = Arc::new(redact);
+        self
+    }
+}
+
+impl BodyCaptureConfig {
+    fn is_capturable(&self, headers: &HeaderMap, body: &Body) -> bool {
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+        let Some(content_type) = content_type else {
+            return false;
+        };
+        self.content_types.contains(&content_type)
+            && body
+                .size_hint()
+                .upper()
+                .is_some_and(|size| size <= self.max_body_size as u64)
+    }
+
+    // Buffer a body, or on failure give it back with the bytes read so far replayed in front
+    async fn buffer(
+        &self,
+        name: &'static str,
+        body: Body,
+        span: &tracing::Span,
+    ) -> (Body, Option<Bytes>) {
+        match buffer(body, self.max_body_size).await {
+            Ok(bytes) => (Body::from(bytes.clone()), Some(bytes)),
+            Err((body, error)) => {
+                span.add_event(
+                    format!("{name}.capture_failed"),
+                    vec![KeyValue::new("error.message", error)],
+                );
+                (body, None)
+            }
+        }
+    }
+
+    fn event(&self, name: &'static str, bytes: &Bytes) -> (&'static str, Vec<KeyValue>) {
+        let body = (self.redact)(&String::from_utf8_lossy(bytes));
+        (
+            name,
+            vec![
+                KeyValue::new(format!("{name}.size"), bytes.len() as i64),
+                KeyValue::new(format!("{name}.content"), body),
+            ],
+        )
+    }
+}
+
+impl<S> Layer<S> for BodyCaptureLayer {
+    type Service = BodyCaptureService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyCaptureService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// The [`Service`] created by [`BodyCaptureLayer`].
+#[derive(Clone)]
+pub struct BodyCaptureService<S> {
+    inner: S,
+    config: Arc<BodyCaptureConfig>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for BodyCaptureService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyCaptureService")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Service<http::Request<Body>> for BodyCaptureService<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        // Take the service that was driven to readiness and leave a clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let config = self.config.clone();
+        let span = tracing::Span::current();
+
+        Box::pin(async move {
+            let debug = config
+                .debug_header
+                .as_ref()
+                .is_some_and(|debug_header| request.headers().contains_key(debug_header));
+            let (request, request_body) = if config.is_capturable(request.headers(), request.body())
+            {
+                let (parts, body) = request.into_parts();
+                let (body, bytes) = config.buffer("http.request.body", body, &span).await;
+                (http::Request::from_parts(parts, body), bytes)
+            } else {
+                (request, None)
+            };
+
+            let response = inner.call(request).await?;
+
+            let status = response.status();
+            if !(debug || status.is_client_error() || status.is_server_error()) {
+                return Ok(response);
+            }
+            if let Some(bytes) = request_body {
+                let (name, attributes) = config.event("http.request.body", &bytes);
+                span.add_event(name, attributes);
+            }
+            if !config.is_capturable(response.headers(), response.body()) {
+                return Ok(response);
+            }
+            let (parts, body) = response.into_parts();
+            let (body, bytes) = config.buffer("http.response.body", body, &span).await;
+            if let Some(bytes) = bytes {
+                let (name, attributes) = config.event("http.response.body", &bytes);
+                span.add_event(name, attributes);
+            }
+            Ok(http::Response::from_parts(parts, body))
+        })
+    }
+}
+
+// Read a body of at most `limit` bytes. When it fails or turns out larger than its size hint,
+// the frames read so far are replayed in front of the error or of the rest of the body.
+async fn buffer(mut body: Body, limit: usize) -> Result<Bytes, (Body, String)> {
+    let mut frames = VecDeque::new();
+    let mut size = 0;
+    loop {
+        match poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+            Some(Ok(frame)) => {
+                size += frame.data_ref().map_or(0, Bytes::len);
+                frames.push_back(frame);
+                if size > limit {
+                    let body = Replay {
+                        frames,
+                        rest: Some(Ok(body)),
+                    };
+                    return Err((Body::new(body), "length limit exceeded".to_string()));
+                }
+            }
+            Some(Err(error)) => {
+                let message = error.to_string();
+                let body = Replay {
+                    frames,
+                    rest: Some(Err(error)),
+                };
+                return Err((Body::new(body), message));
+            }
+            None => break,
+        }
+    }
+    let mut bytes = Vec::with_capacity(size);
+    for frame in frames {
+        if let Ok(data) = frame.into_data() {
+            bytes.extend_from_slice(&data);
+        }
+    }
+    Ok(bytes.into())
+}
+
+// The frames already read from a body, followed by the rest of the body or its error
+struct Replay {
+    frames: VecDeque<Frame<Bytes>>,
+    rest: Option<Result<Body, axum::Error>>,
+}
+
+impl HttpBody for Replay {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        if let Some(frame) = self.frames.pop_front() {
+            return Poll::Ready(Some(Ok(frame)));
+        }
+        match &mut self.rest {
+            Some(Ok(body)) => Pin::new(body).poll_frame(cx),
+            Some(Err(_)) => Poll::Ready(self.rest.take().and_then(Result::err).map(Err)),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// Replaces the values of sensitive fields in a JSON or form encoded body with `[REDACTED]`.
+///
+/// A field is sensitive when its lowercase name contains `authorization`, `password`,
+/// `secret`, `token`, `api_key` or `apikey`. Form keys are percent-decoded before they are
+/// checked. Bodies that are neither valid JSON nor form encoded are returned unchanged.
+///
+/// A JSON body is re-serialized, so its whitespace is removed and the keys of its objects are
+/// sorted, as the example shows.
+///
+/// # Example
+///
+/// ```rust
+/// use axum_otel::redact_sensitive_fields;
+///
+/// assert_eq!(
+///     redact_sensitive_fields(r#"{"user":"alice","password":"hunter2"}"#),
+///     r#"{"password":"[REDACTED]","user":"alice"}"#
+/// );
+/// assert_eq!(
+///     redact_sensitive_fields("user=alice&access_token=abc"),
+///     "user=alice&access_token=[REDACTED]"
+/// );
+/// ```
+pub fn redact_sensitive_fields(body: &str) -> String {
+    if let Ok(mut value) = serde_json::from_str::<serde_json::Value>(body) {
+        redact_json(&mut value);
+        return value.to_string();
+    }
+    if body.contains('=') && !body.contains(char::is_whitespace) {
+        return body
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if is_sensitive(&form_decode(key)) => format!("{key}={REDACTED}"),
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+    }
+    body.to_string()
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive(key) {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+// Decode a form encoded key, keeping malformed escapes as they are
+fn form_decode(key: &str) -> String {
+    let input = key.as_bytes();
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        let hex = input
+            .get(index + 1..index + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (input[index], hex) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                index += 3;
+            }
+            (b'+', _) => {
+                bytes.push(b' ');
+                index += 1;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEYS
+        .iter()
+        .any(|sensitive| key.contains(sensitive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::span_exporter, AxumOtelSpanCreator};
+    use axum::{body::to_bytes, http::StatusCode, routing::post, Json, Router};
+    use tower::ServiceExt;
+    use tower_http::trace::TraceLayer;
+
+    #[test]
+    fn test_redact_sensitive_fields() {
+        assert_eq!(
+            redact_sensitive_fields(r#"{"items":[{"Api_Key":"k"}],"name":"n"}"#),
+            r#"{"items":[{"Api_Key":"[REDACTED]"}],"name":"n"}"#
+        );
+        assert_eq!(
+            redact_sensitive_fields("password=p&name=n"),
+            "password=[REDACTED]&name=n"
+        );
+        assert_eq!(
+            redact_sensitive_fields("pass%77ord=p&api%5Fkey=k&name%3=n"),
+            "pass%77ord=[REDACTED]&api%5Fkey=[REDACTED]&name%3=n"
+        );
+        assert_eq!(redact_sensitive_fields("plain text"), "plain text");
+    }
+
+    async fn handler(Json(body): Json<serde_json::Value>) -> (StatusCode, Json<serde_json::Value>) {
+        (StatusCode::BAD_REQUEST, Json(body))
+    }
+
+    #[tokio::test]
+    async fn test_body_capture_on_error() {
+        let (exporter, _default) = span_exporter();
+
+        let app = Router::new().route("/", post(handler)).layer(
+            tower::ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http().make_span_with(AxumOtelSpanCreator::new()))
+                .layer(BodyCaptureLayer::new()),
+        );
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"password":"hunter2"}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"password":"hunter2"}"#);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let events: Vec<_> = spans[0]
+            .events
+            .iter()
+            .filter(|event| event.name.starts_with("http."))
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name, "http.request.body");
+        assert_eq!(events[1].name, "http.response.body");
+        let content = events[0]
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == "http.request.body.content")
+            .unwrap();
+        assert_eq!(content.value.as_str(), r#"{"password":"[REDACTED]"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_debug_header() {
+        let (exporter, _default) = span_exporter();
+        let captured = |layer: BodyCaptureLayer| {
+            let app = Router::new()
+                .route("/", post(|body: String| async move { body }))
+                .layer(
+                    tower::ServiceBuilder::new()
+                        .layer(
+                            TraceLayer::new_for_http().make_span_with(AxumOtelSpanCreator::new()),
+                        )
+                        .layer(layer),
+                );
+            let request = http::Request::builder()
+                .method("POST")
+                .uri("/")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(X_DEBUG_BODY, "1")
+                .body(Body::from("{}"))
+                .unwrap();
+            let exporter = exporter.clone();
+            async move {
+                drop(app.oneshot(request).await.unwrap());
+                let spans = exporter.get_finished_spans().unwrap();
+                exporter.reset();
+                spans[0]
+                    .events
+                    .iter()
+                    .any(|event| event.name == "http.request.body")
+            }
+        };
+
+        // ignored unless it is configured
+        assert!(!captured(BodyCaptureLayer::new()).await);
+        assert!(captured(BodyCaptureLayer::new().debug_header(X_DEBUG_BODY)).await);
+    }
+
+    // A body which fails after its first chunk
+    struct Failing(Option<Bytes>);
+
+    impl HttpBody for Failing {
+        type Data = Bytes;
+        type Error = std::io::Error;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+            Poll::Ready(Some(match self.0.take() {
+                Some(chunk) => Ok(Frame::data(chunk)),
+                None => Err(std::io::Error::other("connection reset")),
+            }))
+        }
+
+        fn size_hint(&self) -> http_body::SizeHint {
+            http_body::SizeHint::with_exact(16)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_capture_failure_is_passed_on() {
+        let (exporter, _default) = span_exporter();
+
+        let app = Router::new()
+            .route("/", post(|body: Bytes| async move { body }))
+            .layer(
+                tower::ServiceBuilder::new()
+                    .layer(TraceLayer::new_for_http().make_span_with(AxumOtelSpanCreator::new()))
+                    .layer(BodyCaptureLayer::new()),
+            );
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::new(Failing(Some(Bytes::from_static(b"{\"a\":")))))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        // the handler sees the error instead of an empty body
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        drop(response);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let event = spans[0]
+            .events
+            .iter()
+            .find(|event| event.name == "http.request.body.capture_failed")
+            .expect("the capture failure should be recorded");
+        assert_eq!(event.attributes[0].value.as_str(), "connection reset");
+        assert!(spans[0]
+            .events
+            .iter()
+            .all(|event| event.name != "http.request.body"));
+    }
+
+    #[tokio::test]
+    async fn test_buffer_replays_frames_before_the_error() {
+        let body = Body::new(Failing(Some(Bytes::from_static(b"partial"))));
+        let (mut body, error) = buffer(body, DEFAULT_MAX_BODY_SIZE).await.unwrap_err();
+        assert_eq!(error, "connection reset");
+        let frame = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.into_data().unwrap(), "partial");
+        assert!(poll_fn(|cx| Pin::new(&mut body).poll_frame(cx))
+            .await
+            .unwrap()
+            .is_err());
+    }
+}
@@ -0,0 +1,341 @@
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    http,
+};
+use http_body::{Frame, SizeHint};
+use pin_project_lite::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// A [`Layer`] which follows the request and response bodies to the end of their stream.
+///
+/// `tower-http` only reports the chunks of a plain HTTP response body, it never tells the
+/// hooks whether the body reached its end or was dropped half way, e.g. because the client
+/// disconnected. This layer wraps both bodies and adds the following attributes to the span:
+///
+/// - `http.request.body.size`: The number of bytes read from the request body, so chunked
+///   uploads without a `Content-Length` header are measured too
+/// - `http.response.body.incomplete`: Set to `true` when the response body is dropped before
+///   the end of its stream, in which case `otel.status_code` is set to "ERROR" as well
+///
+/// It must be added inside the `TraceLayer`, so that the attributes end up on the request span.
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use axum_otel::{AxumOtelOnBodyChunk, AxumOtelSpanCreator, BodyTrackingLayer};
+/// use tower::ServiceBuilder;
+/// use tower_http::trace::TraceLayer;
+///
+/// async fn handler() -> &'static str {
+///     "Hello, world!"
+/// }
+///
+/// let app: Router<()> = Router::new().route("/", get(handler)).layer(
+///     ServiceBuilder::new()
+///         .layer(
+///             TraceLayer::new_for_http()
+///                 .make_span_with(AxumOtelSpanCreator::new())
+///                 .on_body_chunk(AxumOtelOnBodyChunk::new()),
+///         )
+///         .layer(BodyTrackingLayer::new()),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BodyTrackingLayer;
+
+impl BodyTrackingLayer {
+    /// Create a new `BodyTrackingLayer`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for BodyTrackingLayer {
+    type Service = BodyTrackingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyTrackingService { inner }
+    }
+}
+
+/// The [`Service`] created by [`BodyTrackingLayer`].
+#[derive(Clone, Debug)]
+pub struct BodyTrackingService<S> {
+    inner: S,
+}
+
+impl<S> Service<http::Request<Body>> for BodyTrackingService<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<Body>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BodyTrackingFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let span = tracing::Span::current();
+        let request =
+            request.map(|body| Body::new(TrackedBody::new(body, span.clone(), Side::Request)));
+        BodyTrackingFuture {
+            inner: self.inner.call(request),
+            span: Some(span),
+        }
+    }
+}
+
+pin_project! {
+    /// The response future of [`BodyTrackingService`].
+    #[derive(Debug)]
+    pub struct BodyTrackingFuture<F> {
+        #[pin]
+        inner: F,
+        span: Option<tracing::Span>,
+    }
+}
+
+impl<F, E> Future for BodyTrackingFuture<F>
+where
+    F: Future<Output = Result<http::Response<Body>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.inner.poll(cx))?;
+        let Some(span) = this.span.take() else {
+            return Poll::Ready(Ok(response));
+        };
+        Poll::Ready(Ok(response.map(|body| {
+            Body::new(TrackedBody::new(body, span, Side::Response))
+        })))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Request,
+    Response,
+}
+
+// A body which records on the span how far it was read once it is dropped
+struct TrackedBody {
+    inner: Body,
+    span: tracing::Span,
+    side: Side,
+    bytes: u64,
+    eos: bool,
+}
+
+impl TrackedBody {
+    fn new(inner: Body, span: tracing::Span, side: Side) -> Self {
+        Self {
+            inner,
+            span,
+            side,
+            bytes: 0,
+            eos: false,
+        }
+    }
+}
+
+impl HttpBody for TrackedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let frame = ready!(Pin::new(&mut this.inner).poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    this.bytes += data.len() as u64;
+                }
+            }
+            Some(Err(_)) => {}
+            None => this.eos = true,
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for TrackedBody {
+    fn drop(&mut self) {
+        // hyper stops polling a body as soon as it reports the end of its stream
+        let eos = self.eos || self.inner.is_end_stream();
+        match self.side {
+            Side::Request if eos => {
+                self.span.record("http.request.body.size", self.bytes);
+            }
+            Side::Response if !eos => {
+                self.span.record("http.response.body.incomplete", true);
+                self.span.record("otel.status_code", "ERROR");
+                self.span.record(
+                    "otel.status_message",
+                    "response body dropped before the end of the stream",
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{attribute, span_exporter},
+        AxumOtelOnBodyChunk, AxumOtelOnRequest, AxumOtelSpanCreator,
+    };
+    use axum::{
+        body::to_bytes,
+        routing::{get, post},
+        Router,
+    };
+    use std::{collections::VecDeque, convert::Infallible};
+    use tower::ServiceExt;
+    use tower_http::trace::TraceLayer;
+
+    // A body sent in the given chunks, without a known size
+    struct Chunks(VecDeque<Bytes>);
+
+    impl HttpBody for Chunks {
+        type Data = Bytes;
+        type Error = Infallible;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+            Poll::Ready(self.0.pop_front().map(|chunk| Ok(Frame::data(chunk))))
+        }
+    }
+
+    fn chunks(chunks: &[&'static str]) -> Body {
+        Body::new(Chunks(chunks.iter().copied().map(Bytes::from).collect()))
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/stream", get(|| async { chunks(&["a", "bb", "ccc"]) }))
+            .route("/upload", post(|body: String| async move { body }))
+            .layer(
+                tower::ServiceBuilder::new()
+                    .layer(
+                        TraceLayer::new_for_http()
+                            .make_span_with(AxumOtelSpanCreator::new())
+                            .on_request(AxumOtelOnRequest::new())
+                            .on_body_chunk(AxumOtelOnBodyChunk::new()),
+                    )
+                    .layer(BodyTrackingLayer::new()),
+            )
+    }
+
+    fn request(method: &str, uri: &str, body: Body) -> http::Request<Body> {
+        http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(body)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_streamed_response_to_the_end() {
+        let (exporter, _default) = span_exporter();
+        let response = app()
+            .oneshot(request("GET", "/stream", Body::empty()))
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"abbccc");
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            attribute(&spans[0].attributes, "http.response.body.chunks").as_deref(),
+            Some("3")
+        );
+        assert_eq!(
+            attribute(&spans[0].attributes, "http.response.body.size").as_deref(),
+            Some("6")
+        );
+        assert_eq!(
+            attribute(&spans[0].attributes, "http.response.body.incomplete"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streamed_response_dropped_early() {
+        let (exporter, _default) = span_exporter();
+        let response = app()
+            .oneshot(request("GET", "/stream", Body::empty()))
+            .await
+            .unwrap();
+        let mut body = response.into_body();
+        std::future::poll_fn(|cx| Pin::new(&mut body).poll_frame(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        drop(body);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            attribute(&spans[0].attributes, "http.response.body.chunks").as_deref(),
+            Some("1")
+        );
+        assert_eq!(
+            attribute(&spans[0].attributes, "http.response.body.incomplete").as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            spans[0].status,
+            opentelemetry::trace::Status::error(
+                "response body dropped before the end of the stream"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_size() {
+        let (exporter, _default) = span_exporter();
+        let response = app()
+            .oneshot(request("POST", "/upload", chunks(&["ab", "cd", "ef"])))
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"abcdef");
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            attribute(&spans[0].attributes, "http.request.body.size").as_deref(),
+            Some("6")
+        );
+        assert_eq!(
+            attribute(&spans[0].attributes, "http.response.body.incomplete"),
+            None
+        );
+    }
+}
@@ -24,6 +24,9 @@
 //! - Request ID tracking
 //! - Customizable span attributes
 //! - Error tracking
+//! - Body size and streaming metrics
+//! - User identity enrichment
+//! - Opt-in request and response body capture
 //!
 //! ## Usage
 //!
@@ -32,7 +35,10 @@
 //!     routing::get,
 //!     Router,
 //! };
-//! use axum_otel::{AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator, Level};
+//! use axum_otel::{
+//!     AxumOtelOnBodyChunk, AxumOtelOnFailure, AxumOtelOnRequest, AxumOtelOnResponse,
+//!     AxumOtelSpanCreator, Level,
+//! };
 //! use tower_http::trace::TraceLayer;
 //!
 //! async fn handler() -> &'static str {
@@ -45,6 +51,8 @@
 //!     .layer(
 //!         TraceLayer::new_for_http()
 //!             .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO))
+//!             .on_request(AxumOtelOnRequest::new())
+//!             .on_body_chunk(AxumOtelOnBodyChunk::new())
 //!             .on_response(AxumOtelOnResponse::new().level(Level::INFO))
 //!             .on_failure(AxumOtelOnFailure::new()),
 //!     );
@@ -55,17 +63,64 @@
 //! - [`AxumOtelSpanCreator`] - Creates spans for each request with relevant HTTP information
 //! - [`AxumOtelOnResponse`] - Records response status and latency
 //! - [`AxumOtelOnFailure`] - Handles error cases and updates span status
+//! - [`AxumOtelOnRequest`] - Records the request body size
+//! - [`AxumOtelOnBodyChunk`] - Records the response body size, time-to-first-byte and stream duration
+//! - [`AxumOtelOnEos`] - Emits an event when a streaming response completes
+//! - [`BodyTrackingLayer`] - Measures chunked uploads and flags response bodies dropped before the end of the stream
+//! - [`RequestSpanLayer`] and [`RequestSpan`] - Record attributes such as the user identity on the request span
+//! - [`BodyCaptureLayer`] - Attaches redacted request and response bodies to errored spans for debugging
+//!
+//! ## Streaming and early-ended bodies
+//!
+//! With [`TraceLayer::new_for_http`], `tower-http` classifies a response as soon as its headers
+//! are sent: [`AxumOtelOnEos`] is never called and nothing tells the hooks whether the body
+//! reached its end. Add [`BodyTrackingLayer`] inside the `TraceLayer` to set
+//! `http.response.body.incomplete` and an error status on responses whose body is dropped half
+//! way, e.g. when the client disconnects. Without it, such responses are reported as
+//! successful.
+//!
+//! [`TraceLayer::new_for_http`]: tower_http::trace::TraceLayer::new_for_http
 //!
 //! See the [examples](https://github.com/iamnivekx/axum-otel/tree/main/examples) directory for complete examples.
 //!
+mod body_capture;
+mod body_tracking;
 mod make_span;
+mod on_body_chunk;
+mod on_eos;
 mod on_failure;
+mod on_request;
 mod on_response;
+mod request_span;
+#[cfg(test)]
+mod testing;
 
 // Exports for the tower-http::trace::TraceLayer based middleware
-pub use make_span::AxumOtelSpanCreator;
+pub use body_capture::{
+    redact_sensitive_fields, BodyCaptureLayer, BodyCaptureService, DEFAULT_MAX_BODY_SIZE,
+    X_DEBUG_BODY,
+};
+pub use body_tracking::{BodyTrackingFuture, BodyTrackingLayer, BodyTrackingService};
+pub use make_span::{AxumOtelSpanCreator, MakeSpanAttributes};
+pub use on_body_chunk::AxumOtelOnBodyChunk;
+pub use on_eos::AxumOtelOnEos;
 pub use on_failure::AxumOtelOnFailure;
+pub use on_request::AxumOtelOnRequest;
 pub use on_response::AxumOtelOnResponse;
+pub use request_span::{RequestSpan, RequestSpanFuture, RequestSpanLayer, RequestSpanService};
 
 // Re-export the Level enum from tracing crate
 pub use tracing::Level;
+
+// Items used by the exported macros
+#[doc(hidden)]
+pub mod __private {
+    pub use axum::extract::{ConnectInfo, MatchedPath};
+    pub use opentelemetry::trace::SpanKind;
+    pub use std::net::SocketAddr;
+    pub use tracing::field::Empty;
+    pub use tracing_otel_extra::{
+        dyn_span,
+        extract::{context, fields},
+    };
+}
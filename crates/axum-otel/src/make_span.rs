@@ -1,15 +1,95 @@
-use axum::{
-    extract::{ConnectInfo, MatchedPath},
-    http,
-};
-use opentelemetry::trace::SpanKind;
-use std::net::SocketAddr;
+use axum::http;
+use opentelemetry::KeyValue;
+use std::fmt;
 use tower_http::trace::MakeSpan;
-use tracing::{field::Empty, Level};
-use tracing_otel_extra::{
-    dyn_span,
-    extract::{context, fields},
-};
+use tracing::Level;
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+/// Creates a request span with the standard `axum-otel` attributes plus extra field slots.
+///
+/// `tracing` only accepts values for fields that were declared when the span was created,
+/// anything recorded later under another name is silently dropped. Use this macro in a custom
+/// [`MakeSpan`] closure to declare the extra slots that handlers or middleware will fill in
+/// through [`RequestSpan::record`].
+///
+/// The standard attributes take 25 fields. `tracing` versions before 0.1.39 accept at most 32
+/// fields per span, so declare at most 7 extra slots if the calling crate may resolve one of
+/// them; later versions have no such limit.
+///
+/// Like [`dyn_span!`], the expansion uses the `tracing` crate, so it must be a dependency of
+/// the calling crate.
+///
+/// [`RequestSpan::record`]: crate::RequestSpan::record
+/// [`dyn_span!`]: tracing_otel_extra::dyn_span
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{body::Body, http::Request};
+/// use axum_otel::{request_span, Level};
+/// use tower_http::trace::TraceLayer;
+/// use tracing::field::Empty;
+///
+/// let layer = TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
+///     request_span!(Level::INFO, request, app.plan = Empty, app.region = Empty)
+/// });
+/// ```
+#[macro_export]
+macro_rules! request_span {
+    ($level:expr, $request:expr $(, $($field:tt)*)?) => {{
+        use $crate::__private::{context, fields, Empty};
+
+        let request = $request;
+        let http_method = request.method().as_str();
+        let http_route = request
+            .extensions()
+            .get::<$crate::__private::MatchedPath>()
+            .map(|p| p.as_str());
+
+        let client_ip = request
+            .extensions()
+            .get::<$crate::__private::ConnectInfo<$crate::__private::SocketAddr>>()
+            .map(|$crate::__private::ConnectInfo(ip)| tracing::field::debug(ip));
+
+        let span_name = http_route.as_ref().map_or_else(
+            || http_method.to_string(),
+            |route| format!("{http_method} {route}"),
+        );
+
+        let span = $crate::__private::dyn_span!(
+            $level,
+            "request",
+            enduser.id = Empty,
+            enduser.role = Empty,
+            http.client_ip = client_ip,
+            http.versions = ?request.version(),
+            http.host = ?fields::extract_host(request),
+            http.method = ?fields::extract_http_method(request),
+            http.request.body.size = Empty,
+            http.response.body.chunks = Empty,
+            http.response.body.incomplete = Empty,
+            http.response.body.size = Empty,
+            http.response.stream_duration_ms = Empty,
+            http.response.time_to_first_byte_ms = Empty,
+            http.route = http_route,
+            http.scheme = ?fields::extract_http_scheme(request),
+            http.status_code = Empty,
+            http.target = request.uri().path_and_query().map(|p| p.as_str()),
+            http.user_agent = ?fields::extract_user_agent(request),
+            otel.name = span_name,
+            otel.kind = ?$crate::__private::SpanKind::Server,
+            otel.status_code = Empty,
+            otel.status_message = Empty,
+            request_id = %fields::extract_request_id(request),
+            slow_request = Empty,
+            tenant.id = Empty,
+            trace_id = Empty,
+            $($($field)*)?
+        );
+        context::set_otel_parent(request.headers(), &span);
+        span
+    }};
+}
 
 /// An implementor of [`MakeSpan`] which creates `tracing` spans populated with information about
 /// the request received by an `axum` web server.
@@ -26,6 +106,18 @@ use tracing_otel_extra::{
 /// - `request_id`: A unique request identifier
 /// - `trace_id`: The OpenTelemetry trace ID
 ///
+/// It also declares empty slots for the body and streaming attributes recorded by
+/// [`AxumOtelOnRequest`], [`AxumOtelOnBodyChunk`] and [`BodyTrackingLayer`], and for the identity attributes
+/// `enduser.id`, `enduser.role` and `tenant.id` recorded through [`RequestSpan`].
+/// Use [`request_span!`] to declare further slots.
+///
+/// [`AxumOtelOnRequest`]: crate::AxumOtelOnRequest
+/// [`AxumOtelOnBodyChunk`]: crate::AxumOtelOnBodyChunk
+/// [`BodyTrackingLayer`]: crate::BodyTrackingLayer
+/// [`RequestSpan`]: crate::RequestSpan
+///
+/// Service-specific attributes can be added with [`AxumOtelSpanCreator::attributes`].
+///
 /// # Example
 ///
 /// ```rust
@@ -35,9 +127,10 @@ use tracing_otel_extra::{
 /// let layer = TraceLayer::new_for_http()
 ///     .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO));
 /// ```
-#[derive(Clone, Copy, Debug)]
-pub struct AxumOtelSpanCreator {
+#[derive(Clone, Copy)]
+pub struct AxumOtelSpanCreator<A = ()> {
     level: Level,
+    attributes: A,
 }
 
 impl AxumOtelSpanCreator {
@@ -45,9 +138,12 @@ impl AxumOtelSpanCreator {
     pub fn new() -> Self {
         Self {
             level: Level::TRACE,
+            attributes: (),
         }
     }
+}
 
+impl<A> AxumOtelSpanCreator<A> {
     /// Set the [`Level`] used for [tracing events].
     ///
     /// Defaults to [`Level::TRACE`].
@@ -57,6 +153,43 @@ impl AxumOtelSpanCreator {
         self.level = level;
         self
     }
+
+    /// Set a [`MakeSpanAttributes`] implementation, usually a closure, which returns extra
+    /// attributes for each request.
+    ///
+    /// The attributes are set directly on the exported OpenTelemetry span, so their keys do
+    /// not need to be declared up front. They do not show up as log fields.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use axum::{body::Body, http::Request};
+    /// use axum_otel::AxumOtelSpanCreator;
+    /// use opentelemetry::KeyValue;
+    /// use tower_http::trace::TraceLayer;
+    ///
+    /// let layer = TraceLayer::new_for_http().make_span_with(
+    ///     AxumOtelSpanCreator::new().attributes(|request: &Request<Body>| {
+    ///         let mut attributes = Vec::new();
+    ///         if request.uri().path().starts_with("/v2/") {
+    ///             attributes.push(KeyValue::new("api.version", "v2"));
+    ///         }
+    ///         if let Some(flag) = request.headers().get("x-feature-flag") {
+    ///             attributes.push(KeyValue::new(
+    ///                 "feature_flag",
+    ///                 flag.to_str().unwrap_or_default().to_string(),
+    ///             ));
+    ///         }
+    ///         attributes
+    ///     }),
+    /// );
+    /// ```
+    pub fn attributes<F>(self, attributes: F) -> AxumOtelSpanCreator<F> {
+        AxumOtelSpanCreator {
+            level: self.level,
+            attributes,
+        }
+    }
 }
 
 impl Default for AxumOtelSpanCreator {
@@ -65,43 +198,115 @@ impl Default for AxumOtelSpanCreator {
     }
 }
 
-impl<B> MakeSpan<B> for AxumOtelSpanCreator {
+impl<A> fmt::Debug for AxumOtelSpanCreator<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AxumOtelSpanCreator")
+            .field("level", &self.level)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns extra attributes for the span created by [`AxumOtelSpanCreator`].
+///
+/// It is implemented for closures taking `&http::Request<B>` and returning a `Vec<KeyValue>`,
+/// and for `()`, which adds no attributes.
+pub trait MakeSpanAttributes<B> {
+    /// Returns the attributes for the given request.
+    fn make_span_attributes(&mut self, request: &http::Request<B>) -> Vec<KeyValue>;
+}
+
+impl<B> MakeSpanAttributes<B> for () {
+    fn make_span_attributes(&mut self, _request: &http::Request<B>) -> Vec<KeyValue> {
+        Vec::new()
+    }
+}
+
+impl<B, F> MakeSpanAttributes<B> for F
+where
+    F: FnMut(&http::Request<B>) -> Vec<KeyValue>,
+{
+    fn make_span_attributes(&mut self, request: &http::Request<B>) -> Vec<KeyValue> {
+        self(request)
+    }
+}
+
+impl<B, A> MakeSpan<B> for AxumOtelSpanCreator<A>
+where
+    A: MakeSpanAttributes<B>,
+{
     fn make_span(&mut self, request: &http::Request<B>) -> tracing::Span {
-        let http_method = request.method().as_str();
-        let http_route = request
-            .extensions()
-            .get::<MatchedPath>()
-            .map(|p| p.as_str());
+        let span = request_span!(self.level, request);
+        for attribute in self.attributes.make_span_attributes(request) {
+            span.set_attribute(attribute.key, attribute.value);
+        }
+        span
+    }
+}
 
-        let client_ip = request
-            .extensions()
-            .get::<ConnectInfo<SocketAddr>>()
-            .map(|ConnectInfo(ip)| tracing::field::debug(ip));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{attribute, span_exporter};
+    use axum::body::Body;
 
-        let span_name = http_route.as_ref().map_or_else(
-            || http_method.to_string(),
-            |route| format!("{http_method} {route}"),
+    #[test]
+    fn test_span_creator_attributes() {
+        let (exporter, _default) = span_exporter();
+        let mut span_creator = AxumOtelSpanCreator::new().level(Level::INFO).attributes(
+            |request: &http::Request<Body>| {
+                vec![KeyValue::new(
+                    "api.version",
+                    request
+                        .uri()
+                        .path()
+                        .split('/')
+                        .nth(1)
+                        .unwrap_or_default()
+                        .to_string(),
+                )]
+            },
         );
+        let request = http::Request::builder()
+            .uri("/v2/users")
+            .body(Body::empty())
+            .unwrap();
+        drop(span_creator.make_span(&request));
 
-        let span = dyn_span!(
-            self.level,
-            "request",
-            http.client_ip = client_ip,
-            http.versions = ?request.version(),
-            http.host = ?fields::extract_host(request),
-            http.method = ?fields::extract_http_method(request),
-            http.route = http_route,
-            http.scheme = ?fields::extract_http_scheme(request),
-            http.status_code = Empty,
-            http.target = request.uri().path_and_query().map(|p| p.as_str()),
-            http.user_agent = ?fields::extract_user_agent(request),
-            otel.name = span_name,
-            otel.kind = ?SpanKind::Server,
-            otel.status_code = Empty,
-            request_id = %fields::extract_request_id(request),
-            trace_id = Empty
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            attribute(&spans[0].attributes, "api.version").as_deref(),
+            Some("v2")
+        );
+    }
+    #[test]
+    fn test_request_span_extra_slots() {
+        use tracing::field::Empty;
+
+        let (exporter, _default) = span_exporter();
+        let request = http::Request::builder()
+            .uri("/users")
+            .body(Body::empty())
+            .unwrap();
+        // The most slots which fit next to the standard attributes with any `tracing` 0.1
+        let span = request_span!(
+            Level::INFO,
+            &request,
+            app.a = Empty,
+            app.b = Empty,
+            app.c = Empty,
+            app.d = Empty,
+            app.e = Empty,
+            app.f = Empty,
+            app.g = Empty
+        );
+        span.record("app.g", "last");
+        drop(span);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(
+            attribute(&spans[0].attributes, "app.g").as_deref(),
+            Some("last")
         );
-        context::set_otel_parent(request.headers(), &span);
-        span
     }
 }
@@ -0,0 +1,122 @@
+use axum::body::Bytes;
+use std::time::{Duration, Instant};
+use tower_http::trace::OnBodyChunk;
+use tracing::Level;
+use tracing_otel_extra::dyn_event;
+
+/// An implementor of [`OnBodyChunk`] which records the response body size and streaming metrics.
+///
+/// Original implementation from [tower-http](https://github.com/tower-rs/tower-http/blob/main/tower-http/src/trace/on_body_chunk.rs).
+///
+/// `tower-http` clones this component for every response, so the counters below are
+/// tracked per response body. The totals are recorded once the response body is dropped,
+/// which happens after the last chunk has been sent or when the stream is cut short.
+///
+/// This component adds the following attributes to the span:
+///
+/// - `http.response.body.size`: The total number of bytes sent
+/// - `http.response.body.chunks`: The number of body chunks sent
+/// - `http.response.time_to_first_byte_ms`: The time from the request start to the first chunk
+/// - `http.response.stream_duration_ms`: The time from the first chunk to the last chunk
+///
+/// `tower-http` does not report the end of a plain HTTP response body, so a body dropped half
+/// way looks the same here as a complete one. Add [`BodyTrackingLayer`] to flag those.
+///
+/// [`BodyTrackingLayer`]: crate::BodyTrackingLayer
+///
+/// # Example
+///
+/// ```rust
+/// use axum_otel::AxumOtelOnBodyChunk;
+/// use tower_http::trace::TraceLayer;
+///
+/// let layer = TraceLayer::new_for_http()
+///     .on_body_chunk(AxumOtelOnBodyChunk::new());
+/// ```
+#[derive(Debug, Default)]
+pub struct AxumOtelOnBodyChunk {
+    level: Option<Level>,
+    stream: Option<StreamState>,
+}
+
+#[derive(Debug)]
+struct StreamState {
+    span: tracing::Span,
+    time_to_first_byte: Duration,
+    first_chunk_at: Instant,
+    last_chunk_at: Instant,
+    bytes: u64,
+    chunks: u64,
+}
+
+// Every clone starts a fresh stream, otherwise the totals would be recorded twice.
+impl Clone for AxumOtelOnBodyChunk {
+    fn clone(&self) -> Self {
+        Self {
+            level: self.level,
+            stream: None,
+        }
+    }
+}
+
+impl AxumOtelOnBodyChunk {
+    /// Create a new `AxumOtelOnBodyChunk`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emit a [tracing event] at the given [`Level`] for every chunk sent.
+    ///
+    /// By default no per-chunk events are emitted, only the span attributes are recorded.
+    ///
+    /// [tracing event]: https://docs.rs/tracing/latest/tracing/#events
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+}
+
+impl OnBodyChunk<Bytes> for AxumOtelOnBodyChunk {
+    fn on_body_chunk(&mut self, chunk: &Bytes, latency: Duration, span: &tracing::Span) {
+        let now = Instant::now();
+        let stream = self.stream.get_or_insert_with(|| StreamState {
+            span: span.clone(),
+            // The first latency reported by tower-http is measured from the request start.
+            time_to_first_byte: latency,
+            first_chunk_at: now,
+            last_chunk_at: now,
+            bytes: 0,
+            chunks: 0,
+        });
+        stream.last_chunk_at = now;
+        stream.bytes += chunk.len() as u64;
+        stream.chunks += 1;
+
+        if let Some(level) = self.level {
+            dyn_event!(
+                level,
+                size = chunk.len(),
+                latency = %latency.as_millis(),
+                "sent body chunk"
+            );
+        }
+    }
+}
+
+impl Drop for AxumOtelOnBodyChunk {
+    fn drop(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            let span = &stream.span;
+            span.record("http.response.body.size", stream.bytes);
+            span.record("http.response.body.chunks", stream.chunks);
+            span.record(
+                "http.response.time_to_first_byte_ms",
+                stream.time_to_first_byte.as_millis() as u64,
+            );
+            span.record(
+                "http.response.stream_duration_ms",
+                (stream.last_chunk_at - stream.first_chunk_at).as_millis() as u64,
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use axum::http;
+use std::time::Duration;
+use tower_http::trace::OnEos;
+use tracing::Level;
+use tracing_otel_extra::dyn_event;
+
+/// An implementor of [`OnEos`] which emits an event when a streaming response completes.
+///
+/// Original implementation from [tower-http](https://github.com/tower-rs/tower-http/blob/main/tower-http/src/trace/on_eos.rs).
+///
+/// `tower-http` only calls this component when the response classifier waits for the end of
+/// the stream, e.g. with gRPC. Plain HTTP responses are classified as soon as the headers are
+/// sent, so use [`AxumOtelOnBodyChunk`] to observe their body.
+///
+/// [`AxumOtelOnBodyChunk`]: crate::AxumOtelOnBodyChunk
+///
+/// # Example
+///
+/// ```rust
+/// use axum_otel::{AxumOtelOnEos, Level};
+/// use tower_http::trace::TraceLayer;
+///
+/// let layer = TraceLayer::new_for_grpc()
+///     .on_eos(AxumOtelOnEos::new().level(Level::INFO));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct AxumOtelOnEos {
+    level: Level,
+}
+
+impl Default for AxumOtelOnEos {
+    fn default() -> Self {
+        Self {
+            level: Level::DEBUG,
+        }
+    }
+}
+
+impl AxumOtelOnEos {
+    /// Create a new `AxumOtelOnEos`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the [`Level`] used for [tracing events].
+    ///
+    /// Defaults to [`Level::DEBUG`].
+    ///
+    /// [tracing events]: https://docs.rs/tracing/latest/tracing/#events
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl OnEos for AxumOtelOnEos {
+    fn on_eos(
+        self,
+        trailers: Option<&http::HeaderMap>,
+        stream_duration: Duration,
+        _span: &tracing::Span,
+    ) {
+        dyn_event!(
+            self.level,
+            stream_duration = %stream_duration.as_millis(),
+            trailers = trailers.map_or(0, |trailers| trailers.len()),
+            "end of stream"
+        );
+    }
+}
@@ -8,6 +8,10 @@ use tracing_otel_extra::dyn_event;
 ///
 /// This component updates the span's `otel.status_code` to "ERROR" when a server error occurs.
 ///
+/// Errors raised while a response body is being streamed are reported here as well, so a
+/// stream that ends early is flagged as an error and its cause is recorded in
+/// `otel.status_message`.
+///
 /// # Example
 ///
 /// ```rust
@@ -64,6 +68,10 @@ impl OnFailure<ServerErrorsFailureClass> for AxumOtelOnFailure {
             ServerErrorsFailureClass::StatusCode(status) if status.is_server_error() => {
                 span.record("otel.status_code", "ERROR");
             }
+            ServerErrorsFailureClass::Error(error) => {
+                span.record("otel.status_code", "ERROR");
+                span.record("otel.status_message", error);
+            }
             _ => {}
         }
     }
@@ -0,0 +1,70 @@
+use axum::http;
+use tower_http::trace::OnRequest;
+use tracing::Level;
+use tracing_otel_extra::dyn_event;
+
+/// An implementor of [`OnRequest`] which records the request body size and emits an event.
+///
+/// Original implementation from [tower-http](https://github.com/tower-rs/tower-http/blob/main/tower-http/src/trace/on_request.rs).
+///
+/// This component adds the following attributes to the span:
+///
+/// - `http.request.body.size`: The request body size, taken from the `Content-Length` header
+///
+/// Chunked uploads have no `Content-Length` header, add [`BodyTrackingLayer`] to measure them.
+///
+/// [`BodyTrackingLayer`]: crate::BodyTrackingLayer
+///
+/// # Example
+///
+/// ```rust
+/// use axum_otel::{AxumOtelOnRequest, Level};
+/// use tower_http::trace::TraceLayer;
+///
+/// let layer = TraceLayer::new_for_http()
+///     .on_request(AxumOtelOnRequest::new().level(Level::INFO));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct AxumOtelOnRequest {
+    level: Level,
+}
+
+impl Default for AxumOtelOnRequest {
+    fn default() -> Self {
+        Self {
+            level: Level::DEBUG,
+        }
+    }
+}
+
+impl AxumOtelOnRequest {
+    /// Create a new `AxumOtelOnRequest`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the [`Level`] used for [tracing events].
+    ///
+    /// Defaults to [`Level::DEBUG`].
+    ///
+    /// [tracing events]: https://docs.rs/tracing/latest/tracing/#events
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl<B> OnRequest<B> for AxumOtelOnRequest {
+    fn on_request(&mut self, request: &http::Request<B>, span: &tracing::Span) {
+        let body_size = request
+            .headers()
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        if let Some(body_size) = body_size {
+            span.record("http.request.body.size", body_size);
+        }
+
+        dyn_event!(self.level, "started processing request");
+    }
+}
@@ -1,4 +1,15 @@
+use crate::RequestSpan;
 use axum::http;
+use opentelemetry::{
+    global,
+    metrics::{Counter, Meter},
+    KeyValue,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Once, OnceLock},
+    time::Duration,
+};
 use tower_http::trace::OnResponse;
 use tracing::Level;
 use tracing_otel_extra::dyn_event;
@@ -11,25 +22,52 @@ use tracing_otel_extra::dyn_event;
 ///
 /// - `http.status_code`: The response status code
 /// - `otel.status_code`: The OpenTelemetry status code (OK for successful responses)
+/// - `slow_request`: Set to `true` when the latency exceeds the slow request threshold
+///
+/// # Slow requests
+///
+/// When a request takes longer than its threshold, a `WARN` event with the method, route and
+/// latency is emitted and the `http.server.slow_requests` counter is incremented.
+///
+/// The method and route are taken from the [`RequestSpan`] response extension, so they
+/// require the [`RequestSpanLayer`](crate::RequestSpanLayer). Without it, the event and the
+/// counter carry no method or route, the event only inherits them from the fields of the
+/// request span, and per-route thresholds never match, so only the global threshold applies.
+/// A warning is logged once when per-route thresholds are set but the extension is missing.
 ///
 /// # Example
 ///
 /// ```rust
 /// use axum_otel::{AxumOtelOnResponse, Level};
+/// use std::time::Duration;
 /// use tower_http::trace::TraceLayer;
 ///
-/// let layer = TraceLayer::new_for_http()
-///     .on_response(AxumOtelOnResponse::new().level(Level::INFO));
+/// let layer = TraceLayer::new_for_http().on_response(
+///     AxumOtelOnResponse::new()
+///         .level(Level::INFO)
+///         .slow_request_threshold(Duration::from_millis(500))
+///         .route_slow_request_threshold("/reports/{id}", Duration::from_secs(5)),
+/// );
 /// ```
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct AxumOtelOnResponse {
     level: Level,
+    slow_request_threshold: Option<Duration>,
+    route_slow_request_thresholds: Arc<HashMap<String, Duration>>,
+    // Built on the first slow request, so that the global meter provider can be installed
+    // after the handler is created
+    slow_requests: Arc<OnceLock<Counter<u64>>>,
+    missing_request_span: Arc<Once>,
 }
 
 impl Default for AxumOtelOnResponse {
     fn default() -> Self {
         Self {
             level: Level::DEBUG,
+            slow_request_threshold: None,
+            route_slow_request_thresholds: Arc::default(),
+            slow_requests: Arc::default(),
+            missing_request_span: Arc::new(Once::new()),
         }
     }
 }
@@ -55,15 +93,45 @@ impl AxumOtelOnResponse {
         self.level = level;
         self
     }
+
+    /// Set the latency above which a request is reported as slow.
+    ///
+    /// Defaults to no threshold.
+    pub fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Set the slow request threshold for a route, overriding the global threshold.
+    ///
+    /// The route must match the axum route pattern, e.g. `/users/{id}`.
+    pub fn route_slow_request_threshold(
+        mut self,
+        route: impl Into<String>,
+        threshold: Duration,
+    ) -> Self {
+        Arc::make_mut(&mut self.route_slow_request_thresholds).insert(route.into(), threshold);
+        self
+    }
+
+    /// Set the meter of the `http.server.slow_requests` counter.
+    ///
+    /// Defaults to the `axum-otel` meter of the global meter provider.
+    pub fn meter(mut self, meter: &Meter) -> Self {
+        self.slow_requests = Arc::new(OnceLock::from(slow_requests_counter(meter)));
+        self
+    }
+
+    fn slow_request_threshold_for(&self, route: Option<&str>) -> Option<Duration> {
+        route
+            .and_then(|route| self.route_slow_request_thresholds.get(route))
+            .copied()
+            .or(self.slow_request_threshold)
+    }
 }
 
 impl<B> OnResponse<B> for AxumOtelOnResponse {
-    fn on_response(
-        self,
-        response: &http::Response<B>,
-        latency: std::time::Duration,
-        span: &tracing::Span,
-    ) {
+    fn on_response(self, response: &http::Response<B>, latency: Duration, span: &tracing::Span) {
         let status = response.status().as_u16();
         span.record("http.status_code", tracing::field::display(status));
         span.record("otel.status_code", "OK");
@@ -74,5 +142,231 @@ impl<B> OnResponse<B> for AxumOtelOnResponse {
             status = %status,
             "finished processing request"
         );
+
+        let request_span = response.extensions().get::<RequestSpan>();
+        if request_span.is_none() && !self.route_slow_request_thresholds.is_empty() {
+            self.missing_request_span.call_once(|| {
+                tracing::warn!(
+                    "per-route slow request thresholds are ignored without the RequestSpanLayer"
+                )
+            });
+        }
+        let route = request_span.and_then(RequestSpan::route);
+        let Some(threshold) = self.slow_request_threshold_for(route) else {
+            return;
+        };
+        if latency <= threshold {
+            return;
+        }
+
+        let method = request_span.map(|request_span| request_span.method().as_str());
+        span.record("slow_request", true);
+        tracing::warn!(
+            http.method = method,
+            http.route = route,
+            latency = %latency.as_millis(),
+            threshold = %threshold.as_millis(),
+            "slow request"
+        );
+
+        let mut attributes = Vec::with_capacity(2);
+        if let Some(method) = method {
+            attributes.push(KeyValue::new("http.method", method.to_string()));
+        }
+        if let Some(route) = route {
+            attributes.push(KeyValue::new("http.route", route.to_string()));
+        }
+        self.slow_requests
+            .get_or_init(|| slow_requests_counter(&global::meter("axum-otel")))
+            .add(1, &attributes);
+    }
+}
+
+fn slow_requests_counter(meter: &Meter) -> Counter<u64> {
+    meter
+        .u64_counter("http.server.slow_requests")
+        .with_description("Number of requests slower than their configured threshold")
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{attribute, span_exporter},
+        AxumOtelSpanCreator, RequestSpanLayer,
+    };
+    use axum::{body::Body, routing::get, Router};
+    use opentelemetry::metrics::MeterProvider as _;
+    use opentelemetry_sdk::{
+        metrics::{
+            data::{AggregatedMetrics, MetricData},
+            InMemoryMetricExporter, SdkMeterProvider,
+        },
+        trace::SpanData,
+    };
+    use tower::ServiceExt;
+    use tower_http::trace::TraceLayer;
+
+    fn span<'a>(spans: &'a [SpanData], route: &str) -> &'a SpanData {
+        spans
+            .iter()
+            .find(|span| attribute(&span.attributes, "http.route").as_deref() == Some(route))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_slow_request_threshold_for() {
+        let on_response = AxumOtelOnResponse::new();
+        assert_eq!(on_response.slow_request_threshold_for(Some("/users")), None);
+
+        let on_response = on_response
+            .slow_request_threshold(Duration::from_millis(500))
+            .route_slow_request_threshold("/reports/{id}", Duration::from_secs(5));
+        assert_eq!(
+            on_response.slow_request_threshold_for(None),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            on_response.slow_request_threshold_for(Some("/users")),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            on_response.slow_request_threshold_for(Some("/reports/{id}")),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_reported() {
+        let metric_exporter = InMemoryMetricExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metric_exporter.clone())
+            .build();
+
+        let (span_exporter, _default) = span_exporter();
+
+        let app = Router::new()
+            .route(
+                "/reports/{id}",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    "OK"
+                }),
+            )
+            .route("/users", get(|| async { "OK" }))
+            .layer(
+                tower::ServiceBuilder::new()
+                    .layer(
+                        TraceLayer::new_for_http()
+                            .make_span_with(AxumOtelSpanCreator::new())
+                            .on_response(
+                                AxumOtelOnResponse::new()
+                                    .meter(&meter_provider.meter("axum-otel"))
+                                    .slow_request_threshold(Duration::from_secs(60))
+                                    .route_slow_request_threshold(
+                                        "/reports/{id}",
+                                        Duration::from_millis(1),
+                                    ),
+                            ),
+                    )
+                    .layer(RequestSpanLayer::new()),
+            );
+        for uri in ["/reports/42", "/users"] {
+            let request = http::Request::builder()
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            drop(app.clone().oneshot(request).await.unwrap());
+        }
+
+        let spans = span_exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        let users = span(&spans, "/users");
+        assert_eq!(attribute(&users.attributes, "slow_request"), None);
+        assert!(users
+            .events
+            .iter()
+            .all(|event| event.name != "slow request"));
+
+        let reports = span(&spans, "/reports/{id}");
+        assert_eq!(
+            attribute(&reports.attributes, "slow_request").as_deref(),
+            Some("true")
+        );
+        let event = reports
+            .events
+            .iter()
+            .find(|event| event.name == "slow request")
+            .expect("slow request event should be recorded");
+        assert_eq!(
+            attribute(&event.attributes, "level").as_deref(),
+            Some("WARN")
+        );
+        assert_eq!(
+            attribute(&event.attributes, "http.method").as_deref(),
+            Some("GET")
+        );
+        assert_eq!(
+            attribute(&event.attributes, "http.route").as_deref(),
+            Some("/reports/{id}")
+        );
+
+        meter_provider.force_flush().unwrap();
+        let metrics = metric_exporter.get_finished_metrics().unwrap();
+        let metric = metrics
+            .iter()
+            .flat_map(|metrics| metrics.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .find(|metric| metric.name() == "http.server.slow_requests")
+            .unwrap();
+        let AggregatedMetrics::U64(MetricData::Sum(sum)) = metric.data() else {
+            panic!("unexpected data: {:?}", metric.data());
+        };
+        let points: Vec<_> = sum.data_points().collect();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].value(), 1);
+        let attributes: Vec<_> = points[0].attributes().cloned().collect();
+        assert_eq!(
+            attribute(&attributes, "http.method").as_deref(),
+            Some("GET")
+        );
+        assert_eq!(
+            attribute(&attributes, "http.route").as_deref(),
+            Some("/reports/{id}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_request_span_is_reported_once() {
+        let (span_exporter, _default) = span_exporter();
+        let app = Router::new().route("/users", get(|| async { "OK" })).layer(
+            TraceLayer::new_for_http()
+                .make_span_with(AxumOtelSpanCreator::new())
+                .on_response(
+                    AxumOtelOnResponse::new()
+                        .route_slow_request_threshold("/users", Duration::from_secs(60)),
+                ),
+        );
+        for _ in 0..2 {
+            let request = http::Request::builder()
+                .uri("/users")
+                .body(Body::empty())
+                .unwrap();
+            drop(app.clone().oneshot(request).await.unwrap());
+        }
+
+        let spans = span_exporter.get_finished_spans().unwrap();
+        let warnings: usize = spans
+            .iter()
+            .map(|span| {
+                span.events
+                    .iter()
+                    .filter(|event| event.name.contains("RequestSpanLayer"))
+                    .count()
+            })
+            .sum();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(warnings, 1);
     }
 }
@@ -0,0 +1,254 @@
+use axum::{
+    extract::{FromRequestParts, MatchedPath},
+    http::{self, request::Parts, StatusCode},
+};
+use pin_project_lite::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// A handle to the root span of the current request.
+///
+/// [`RequestSpanLayer`] stores it in the request extensions, from where handlers can take it
+/// as an extractor and middleware can read it with `request.extensions().get::<RequestSpan>()`.
+/// Values recorded on it show up on the exported span and in every log line emitted under it.
+///
+/// The layer also copies it into the response extensions, which lets response hooks such as
+/// [`AxumOtelOnResponse`] know the method and matched route of the request.
+///
+/// Only fields declared when the span was created can be recorded. [`AxumOtelSpanCreator`]
+/// declares `enduser.id`, `enduser.role` and `tenant.id`, use [`request_span!`] to declare more.
+///
+/// [`AxumOtelSpanCreator`]: crate::AxumOtelSpanCreator
+/// [`AxumOtelOnResponse`]: crate::AxumOtelOnResponse
+///
+/// # Example
+///
+/// ```rust
+/// use axum::{routing::get, Router};
+/// use axum_otel::{AxumOtelSpanCreator, RequestSpan, RequestSpanLayer};
+/// use tower::ServiceBuilder;
+/// use tower_http::trace::TraceLayer;
+///
+/// async fn handler(span: RequestSpan) -> &'static str {
+///     span.record_enduser_id("user-42").record_tenant_id("acme");
+///     "Hello, world!"
+/// }
+///
+/// let app: Router<()> = Router::new().route("/", get(handler)).layer(
+///     ServiceBuilder::new()
+///         .layer(TraceLayer::new_for_http().make_span_with(AxumOtelSpanCreator::new()))
+///         .layer(RequestSpanLayer::new()),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct RequestSpan {
+    span: tracing::Span,
+    method: http::Method,
+    route: Option<String>,
+}
+
+impl RequestSpan {
+    /// Create a new `RequestSpan` from the given span and the request it was created for.
+    pub fn new<B>(span: tracing::Span, request: &http::Request<B>) -> Self {
+        Self {
+            span,
+            method: request.method().clone(),
+            route: request
+                .extensions()
+                .get::<MatchedPath>()
+                .map(|path| path.as_str().to_string()),
+        }
+    }
+
+    /// Get the underlying span.
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Get the method of the request.
+    pub fn method(&self) -> &http::Method {
+        &self.method
+    }
+
+    /// Get the matched route of the request, if any.
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    /// Record a value for a field declared on the request span.
+    pub fn record<V: tracing::Value>(&self, field: &str, value: V) -> &Self {
+        self.span.record(field, value);
+        self
+    }
+
+    /// Record the `enduser.id` attribute.
+    pub fn record_enduser_id(&self, id: impl fmt::Display) -> &Self {
+        self.record("enduser.id", tracing::field::display(id))
+    }
+
+    /// Record the `enduser.role` attribute.
+    pub fn record_enduser_role(&self, role: impl fmt::Display) -> &Self {
+        self.record("enduser.role", tracing::field::display(role))
+    }
+
+    /// Record the `tenant.id` attribute.
+    pub fn record_tenant_id(&self, tenant_id: impl fmt::Display) -> &Self {
+        self.record("tenant.id", tracing::field::display(tenant_id))
+    }
+}
+
+impl<S> FromRequestParts<S> for RequestSpan
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<RequestSpan>().cloned().ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Missing request span. Is `RequestSpanLayer` added inside the `TraceLayer`?",
+        ))
+    }
+}
+
+/// A [`Layer`] which stores the current request span as a [`RequestSpan`] extension on both
+/// the request and the response.
+///
+/// It must be added inside the `TraceLayer`, so that the span created by the
+/// [`MakeSpan`](tower_http::trace::MakeSpan) implementation is the current span when the
+/// request reaches it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestSpanLayer;
+
+impl RequestSpanLayer {
+    /// Create a new `RequestSpanLayer`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for RequestSpanLayer {
+    type Service = RequestSpanService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestSpanService { inner }
+    }
+}
+
+/// The [`Service`] created by [`RequestSpanLayer`].
+#[derive(Clone, Debug)]
+pub struct RequestSpanService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for RequestSpanService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = RequestSpanFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        let request_span = RequestSpan::new(tracing::Span::current(), &request);
+        request.extensions_mut().insert(request_span.clone());
+        RequestSpanFuture {
+            inner: self.inner.call(request),
+            request_span: Some(request_span),
+        }
+    }
+}
+
+pin_project! {
+    /// The response future of [`RequestSpanService`].
+    #[derive(Debug)]
+    pub struct RequestSpanFuture<F> {
+        #[pin]
+        inner: F,
+        request_span: Option<RequestSpan>,
+    }
+}
+
+impl<F, ResBody, E> Future for RequestSpanFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = ready!(this.inner.poll(cx))?;
+        if let Some(request_span) = this.request_span.take() {
+            response.extensions_mut().insert(request_span);
+        }
+        Poll::Ready(Ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::span_exporter, AxumOtelSpanCreator};
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+    use tower_http::trace::TraceLayer;
+
+    async fn handler(span: RequestSpan) -> &'static str {
+        span.record_enduser_id("user-42")
+            .record_enduser_role("admin")
+            .record_tenant_id("acme");
+        "OK"
+    }
+
+    fn request() -> http::Request<Body> {
+        http::Request::builder()
+            .uri("/")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_span_extractor() {
+        let (exporter, _default) = span_exporter();
+
+        let app = Router::new().route("/", get(handler)).layer(
+            tower::ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http().make_span_with(AxumOtelSpanCreator::new()))
+                .layer(RequestSpanLayer::new()),
+        );
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(response);
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        for (key, value) in [
+            ("enduser.id", "user-42"),
+            ("enduser.role", "admin"),
+            ("tenant.id", "acme"),
+        ] {
+            let attribute = spans[0]
+                .attributes
+                .iter()
+                .find(|attribute| attribute.key.as_str() == key)
+                .unwrap_or_else(|| panic!("{key} should be exported"));
+            assert_eq!(attribute.value.as_str(), value);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_span_extractor_without_layer() {
+        let app = Router::new().route("/", get(handler));
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,417 @@
+//! Opt-in resource detectors.
+//!
+//! Each detector implements the SDK [`ResourceDetector`] and only reports the attributes it
+//! can read, so a detector enabled outside of a container or Kubernetes adds nothing.
+//!
+//! | Detector | Attributes | Source |
+//! |----------|------------|--------|
+//! | [`Detector::Host`] | `host.name`, `host.arch`, `host.id` | Kernel hostname, `/etc/machine-id` |
+//! | [`Detector::Os`] | `os.type`, `os.version`, `os.description` | Kernel release, `/etc/os-release` |
+//! | [`Detector::Process`] | `process.pid`, `process.parent_pid`, `process.executable.name`, `process.executable.path` | The current process |
+//! | [`Detector::Container`] | `container.id` | `/proc/self/cgroup`, then `/proc/self/mountinfo` |
+//! | [`Detector::K8s`] | `k8s.pod.name`, `k8s.pod.uid`, `k8s.namespace.name`, `k8s.node.name` | Downward API environment variables and files |
+
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::{resource::ResourceDetector, Resource};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The resource detectors which can be enabled with
+/// [`get_resource_with_detectors`](crate::get_resource_with_detectors).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Detector {
+    /// See [`HostDetector`].
+    Host,
+    /// See [`OsDetector`].
+    Os,
+    /// See [`ProcessDetector`].
+    Process,
+    /// See [`ContainerDetector`].
+    Container,
+    /// See [`K8sDetector`].
+    K8s,
+}
+
+impl Detector {
+    /// All detectors, in the order their attributes are merged.
+    pub const ALL: [Detector; 5] = [
+        Detector::Host,
+        Detector::Os,
+        Detector::Process,
+        Detector::Container,
+        Detector::K8s,
+    ];
+
+    /// The name of the detector, as parsed by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Detector::Host => "host",
+            Detector::Os => "os",
+            Detector::Process => "process",
+            Detector::Container => "container",
+            Detector::K8s => "k8s",
+        }
+    }
+
+    /// The SDK resource detector, with its default settings.
+    pub fn detector(&self) -> Box<dyn ResourceDetector> {
+        match self {
+            Detector::Host => Box::new(HostDetector),
+            Detector::Os => Box::new(OsDetector),
+            Detector::Process => Box::new(ProcessDetector),
+            Detector::Container => Box::new(ContainerDetector),
+            Detector::K8s => Box::new(K8sDetector::default()),
+        }
+    }
+}
+
+impl fmt::Display for Detector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Detector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "host" => Ok(Detector::Host),
+            "os" => Ok(Detector::Os),
+            "process" => Ok(Detector::Process),
+            "container" => Ok(Detector::Container),
+            "k8s" | "kubernetes" => Ok(Detector::K8s),
+            _ => Err(format!(
+                "Invalid resource detector: '{s}', expected one of host, os, process, container, k8s"
+            )),
+        }
+    }
+}
+
+/// Detects the host name, architecture and machine ID.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostDetector;
+
+impl ResourceDetector for HostDetector {
+    fn detect(&self) -> Resource {
+        let mut attributes = Vec::new();
+        let host_name = read_trimmed("/proc/sys/kernel/hostname")
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .or_else(|| std::env::var("COMPUTERNAME").ok())
+            .filter(|name| !name.is_empty());
+        if let Some(host_name) = host_name {
+            attributes.push(KeyValue::new("host.name", host_name));
+        }
+        attributes.push(KeyValue::new("host.arch", host_arch()));
+        if let Some(id) = read_trimmed("/etc/machine-id") {
+            attributes.push(KeyValue::new("host.id", id));
+        }
+        resource(attributes)
+    }
+}
+
+/// Detects the operating system type, kernel version and distribution.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsDetector;
+
+impl ResourceDetector for OsDetector {
+    fn detect(&self) -> Resource {
+        let os_type = match std::env::consts::OS {
+            "macos" => "darwin",
+            os => os,
+        };
+        let mut attributes = vec![KeyValue::new("os.type", os_type)];
+        if let Some(version) = read_trimmed("/proc/sys/kernel/osrelease") {
+            attributes.push(KeyValue::new("os.version", version));
+        }
+        let description = fs::read_to_string("/etc/os-release")
+            .ok()
+            .and_then(|contents| os_release_pretty_name(&contents));
+        if let Some(description) = description {
+            attributes.push(KeyValue::new("os.description", description));
+        }
+        resource(attributes)
+    }
+}
+
+/// Detects the process ID, parent process ID and executable.
+///
+/// The command line is not reported, as it may contain secrets.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessDetector;
+
+impl ResourceDetector for ProcessDetector {
+    fn detect(&self) -> Resource {
+        let mut attributes = vec![KeyValue::new("process.pid", i64::from(std::process::id()))];
+        #[cfg(unix)]
+        attributes.push(KeyValue::new(
+            "process.parent_pid",
+            i64::from(std::os::unix::process::parent_id()),
+        ));
+        if let Ok(path) = std::env::current_exe() {
+            if let Some(name) = path.file_name() {
+                attributes.push(KeyValue::new(
+                    "process.executable.name",
+                    name.to_string_lossy().into_owned(),
+                ));
+            }
+            attributes.push(KeyValue::new(
+                "process.executable.path",
+                path.to_string_lossy().into_owned(),
+            ));
+        }
+        resource(attributes)
+    }
+}
+
+/// Detects the ID of the container the process runs in from its cgroup (v1) or, failing
+/// that, its mount points (cgroup v2).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContainerDetector;
+
+impl ResourceDetector for ContainerDetector {
+    fn detect(&self) -> Resource {
+        let id = fs::read_to_string("/proc/self/cgroup")
+            .ok()
+            .and_then(|contents| container_id_from_cgroup(&contents))
+            .or_else(|| {
+                fs::read_to_string("/proc/self/mountinfo")
+                    .ok()
+                    .and_then(|contents| container_id_from_mountinfo(&contents))
+            });
+        resource(id.map(|id| KeyValue::new("container.id", id)))
+    }
+}
+
+/// Detects the Kubernetes pod, namespace and node.
+///
+/// Each attribute is read from the first set environment variable, usually populated with
+/// the downward API, then from a file of the downward API volume:
+///
+/// | Attribute | Environment variables | File |
+/// |-----------|-----------------------|------|
+/// | `k8s.pod.name` | `K8S_POD_NAME`, `POD_NAME` | `name` |
+/// | `k8s.pod.uid` | `K8S_POD_UID`, `POD_UID` | `uid` |
+/// | `k8s.namespace.name` | `K8S_NAMESPACE_NAME`, `POD_NAMESPACE` | `namespace` |
+/// | `k8s.node.name` | `K8S_NODE_NAME`, `NODE_NAME` | - |
+///
+/// Inside a cluster, the namespace falls back to the one of the service account and the pod
+/// name to the host name.
+#[derive(Debug, Clone)]
+pub struct K8sDetector {
+    downward_api_dir: PathBuf,
+    service_account_dir: PathBuf,
+}
+
+impl Default for K8sDetector {
+    fn default() -> Self {
+        Self {
+            downward_api_dir: PathBuf::from("/etc/podinfo"),
+            service_account_dir: PathBuf::from("/var/run/secrets/kubernetes.io/serviceaccount"),
+        }
+    }
+}
+
+impl K8sDetector {
+    /// Set the directory the downward API volume is mounted at, `/etc/podinfo` by default.
+    pub fn with_downward_api_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.downward_api_dir = dir.into();
+        self
+    }
+
+    fn detect_with(&self, var: impl Fn(&str) -> Option<String>) -> Vec<KeyValue> {
+        let var = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| var(name).map(|value| value.trim().to_string()))
+                .filter(|value| !value.is_empty())
+        };
+        let in_cluster = var(&["KUBERNETES_SERVICE_HOST"]).is_some();
+        let file = |dir: &Path, name: &str| read_trimmed(dir.join(name));
+
+        let pod_name = var(&["K8S_POD_NAME", "POD_NAME"])
+            .or_else(|| file(&self.downward_api_dir, "name"))
+            .or_else(|| in_cluster.then(|| var(&["HOSTNAME"])).flatten());
+        let pod_uid =
+            var(&["K8S_POD_UID", "POD_UID"]).or_else(|| file(&self.downward_api_dir, "uid"));
+        let namespace = var(&["K8S_NAMESPACE_NAME", "POD_NAMESPACE"])
+            .or_else(|| file(&self.downward_api_dir, "namespace"))
+            .or_else(|| {
+                in_cluster
+                    .then(|| file(&self.service_account_dir, "namespace"))
+                    .flatten()
+            });
+        let node_name = var(&["K8S_NODE_NAME", "NODE_NAME"]);
+
+        [
+            ("k8s.pod.name", pod_name),
+            ("k8s.pod.uid", pod_uid),
+            ("k8s.namespace.name", namespace),
+            ("k8s.node.name", node_name),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some(KeyValue::new(key, value?)))
+        .collect()
+    }
+}
+
+impl ResourceDetector for K8sDetector {
+    fn detect(&self) -> Resource {
+        resource(self.detect_with(|name| std::env::var(name).ok()))
+    }
+}
+
+fn resource(attributes: impl IntoIterator<Item = KeyValue>) -> Resource {
+    Resource::builder_empty()
+        .with_attributes(attributes)
+        .build()
+}
+
+fn read_trimmed(path: impl AsRef<Path>) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_string())
+        .filter(|contents| !contents.is_empty())
+}
+
+// The architecture, as named by the semantic conventions
+fn host_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "arm" => "arm32",
+        "powerpc" => "ppc32",
+        "powerpc64" => "ppc64",
+        arch => arch,
+    }
+}
+
+fn os_release_pretty_name(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|value| {
+            value
+                .trim()
+                .trim_matches('"')
+                .trim_matches('\'')
+                .to_string()
+        })
+        .filter(|value| !value.is_empty())
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+// cgroup v1 lines look like `12:pids:/docker/<id>` or
+// `1:name=systemd:/system.slice/docker-<id>.scope`
+fn container_id_from_cgroup(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let path = line.rsplit_once(':')?.1;
+        let segment = path.rsplit('/').next()?;
+        let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+        let id = ["docker-", "cri-containerd-", "crio-", "libpod-"]
+            .iter()
+            .find_map(|prefix| segment.strip_prefix(prefix))
+            .unwrap_or(segment);
+        is_container_id(id).then(|| id.to_string())
+    })
+}
+
+// With cgroup v2, the runtime mounts `/etc/hostname` and friends from
+// `.../containers/<id>/...`
+fn container_id_from_mountinfo(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let root = line.split_whitespace().nth(3)?;
+        let mut segments = root.split('/');
+        segments.find(|segment| *segment == "containers")?;
+        segments
+            .next()
+            .filter(|id| is_container_id(id))
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3c2b6c0a0f1d4e5b8c7a6f5e4d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c";
+
+    #[test]
+    fn test_container_id_from_cgroup() {
+        let docker = format!("12:pids:/docker/{ID}\n1:name=systemd:/docker/{ID}");
+        assert_eq!(container_id_from_cgroup(&docker).as_deref(), Some(ID));
+
+        let systemd = format!("0::/system.slice/docker-{ID}.scope");
+        assert_eq!(container_id_from_cgroup(&systemd).as_deref(), Some(ID));
+
+        let kubepods =
+            format!("1:name=systemd:/kubepods/burstable/pod1234/cri-containerd-{ID}.scope");
+        assert_eq!(container_id_from_cgroup(&kubepods).as_deref(), Some(ID));
+
+        assert_eq!(
+            container_id_from_cgroup("0::/user.slice/session-2.scope"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_container_id_from_mountinfo() {
+        let mountinfo = format!(
+            "1201 1180 0:63 / / rw,relatime - overlay overlay rw\n\
+             1220 1201 254:1 /var/lib/docker/containers/{ID}/hostname /etc/hostname rw - ext4 /dev/vda1 rw"
+        );
+        assert_eq!(container_id_from_mountinfo(&mountinfo).as_deref(), Some(ID));
+        assert_eq!(
+            container_id_from_mountinfo("1201 1180 0:63 / / rw - overlay overlay rw"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_k8s_detector() {
+        let dir = std::env::temp_dir().join(format!("k8s-detector-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("uid"), "pod-uid\n").unwrap();
+        fs::write(dir.join("namespace"), "from-file").unwrap();
+
+        let detector = K8sDetector::default().with_downward_api_dir(&dir);
+        let attributes = detector.detect_with(|name| match name {
+            "POD_NAME" => Some("checkout-7d9f".to_string()),
+            "K8S_NAMESPACE_NAME" => Some("shop".to_string()),
+            "NODE_NAME" => Some("node-1".to_string()),
+            _ => None,
+        });
+        assert_eq!(
+            attributes,
+            [
+                KeyValue::new("k8s.pod.name", "checkout-7d9f"),
+                KeyValue::new("k8s.pod.uid", "pod-uid"),
+                KeyValue::new("k8s.namespace.name", "shop"),
+                KeyValue::new("k8s.node.name", "node-1"),
+            ]
+        );
+
+        // outside of a cluster, the host name is not taken as the pod name
+        let attributes = K8sDetector::default()
+            .with_downward_api_dir(dir.join("missing"))
+            .detect_with(|name| (name == "HOSTNAME").then(|| "laptop".to_string()));
+        assert!(attributes.is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_os_release_pretty_name() {
+        let contents =
+            "NAME=\"Debian GNU/Linux\"\nPRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\n";
+        assert_eq!(
+            os_release_pretty_name(contents).as_deref(),
+            Some("Debian GNU/Linux 12 (bookworm)")
+        );
+    }
+}
@@ -1,11 +1,91 @@
-use anyhow::Result;
-use opentelemetry_sdk::{metrics::SdkMeterProvider, trace::SdkTracerProvider};
+use opentelemetry_sdk::{
+    error::{OTelSdkError, OTelSdkResult},
+    metrics::SdkMeterProvider,
+    trace::SdkTracerProvider,
+};
+use std::{
+    fmt,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The default deadline of [`OtelGuard::shutdown`] and of dropping the guard.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An OpenTelemetry provider held by an [`OtelGuard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    /// The tracer provider.
+    Tracer,
+    /// The meter provider.
+    Meter,
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Provider::Tracer => f.write_str("tracer provider"),
+            Provider::Meter => f.write_str("meter provider"),
+        }
+    }
+}
+
+/// The error of one provider.
+#[derive(Debug)]
+pub struct ProviderError {
+    /// The provider which failed.
+    pub provider: Provider,
+    /// Why it failed, [`OTelSdkError::Timeout`] if it missed the deadline.
+    pub error: OTelSdkError,
+}
+
+/// The providers which failed to flush or shut down, the others succeeded.
+#[derive(Debug)]
+pub struct ProviderErrors {
+    /// The error of each failed provider.
+    pub errors: Vec<ProviderError>,
+}
+
+impl ProviderErrors {
+    fn check(errors: Vec<ProviderError>) -> Result<(), Self> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { errors })
+        }
+    }
+}
+
+impl fmt::Display for ProviderErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}: {}", error.provider, error.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProviderErrors {}
 
 /// A guard that holds the tracer provider and ensures proper cleanup
-#[derive(Debug, Clone)]
+///
+/// Dropping the guard shuts the providers down like [`OtelGuard::shutdown_with_timeout`],
+/// with the timeout set by [`OtelGuard::with_shutdown_timeout`]: the dropping thread blocks
+/// until the providers are shut down or the deadline passes, and a provider still shutting
+/// down is left to finish on a background thread. Shut the guard down explicitly to handle
+/// the errors, or to avoid blocking an async runtime.
+///
+/// The guard is not `Clone`, so the providers are shut down once, by its owner. Clone the
+/// providers themselves to use them elsewhere.
+#[derive(Debug)]
 pub struct OtelGuard {
     tracer_provider: Option<SdkTracerProvider>,
     meter_provider: Option<SdkMeterProvider>,
+    shutdown_timeout: Duration,
 }
 
 impl OtelGuard {
@@ -17,6 +97,7 @@ impl OtelGuard {
         Self {
             tracer_provider,
             meter_provider,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
         }
     }
 
@@ -32,30 +113,225 @@ impl OtelGuard {
         self
     }
 
-    /// Manually shutdown the tracer provider
-    pub fn shutdown(mut self) -> Result<()> {
-        if let Some(tracer_provider) = self.tracer_provider.take() {
-            tracer_provider.shutdown()?;
+    /// Set the deadline of dropping the guard and of [`OtelGuard::shutdown`].
+    ///
+    /// Defaults to [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// The tracer provider, if tracing is initialized.
+    pub fn tracer_provider(&self) -> Option<&SdkTracerProvider> {
+        self.tracer_provider.as_ref()
+    }
+
+    /// The meter provider, if metrics are initialized.
+    pub fn meter_provider(&self) -> Option<&SdkMeterProvider> {
+        self.meter_provider.as_ref()
+    }
+
+    /// Export the buffered spans and metrics, reporting every provider which failed.
+    pub fn force_flush(&self) -> Result<(), ProviderErrors> {
+        let mut errors = Vec::new();
+        if let Some(Err(error)) = self.tracer_provider.as_ref().map(|p| p.force_flush()) {
+            errors.push(ProviderError {
+                provider: Provider::Tracer,
+                error,
+            });
         }
-        if let Some(meter_provider) = self.meter_provider.take() {
-            meter_provider.shutdown()?;
+        if let Some(Err(error)) = self.meter_provider.as_ref().map(|p| p.force_flush()) {
+            errors.push(ProviderError {
+                provider: Provider::Meter,
+                error,
+            });
         }
-        Ok(())
+        ProviderErrors::check(errors)
+    }
+
+    /// Export the buffered spans and metrics like [`OtelGuard::force_flush`], waiting at most
+    /// `timeout` for them.
+    ///
+    /// The providers are flushed concurrently on their own threads. A provider still
+    /// flushing is reported with [`OTelSdkError::Timeout`] and left to finish in the background.
+    pub fn force_flush_with_timeout(&self, timeout: Duration) -> Result<(), ProviderErrors> {
+        run_with_deadline(
+            timeout,
+            self.tracer_provider.clone(),
+            self.meter_provider.clone(),
+            |tracer_provider| tracer_provider.force_flush(),
+            |meter_provider| meter_provider.force_flush(),
+        )
+    }
+
+    /// Shut down the providers, waiting at most `timeout` for them.
+    ///
+    /// The providers are shut down concurrently on their own threads, so a collector which
+    /// is down cannot block past the deadline. A provider still shutting down is reported
+    /// with [`OTelSdkError::Timeout`] and left to finish in the background.
+    pub fn shutdown_with_timeout(mut self, timeout: Duration) -> Result<(), ProviderErrors> {
+        self.shutdown_providers(timeout)
+    }
+
+    /// Shut down the providers like [`OtelGuard::shutdown_with_timeout`], with the timeout
+    /// set by [`OtelGuard::with_shutdown_timeout`]. Requires the `shutdown` feature.
+    ///
+    /// The blocking shutdown runs on the Tokio blocking thread pool, so it is safe to await
+    /// from any Tokio runtime, including a current-thread one.
+    #[cfg(feature = "shutdown")]
+    pub async fn shutdown(self) -> Result<(), ProviderErrors> {
+        let timeout = self.shutdown_timeout;
+        match tokio::task::spawn_blocking(move || self.shutdown_with_timeout(timeout)).await {
+            Ok(result) => result,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+
+    fn shutdown_providers(&mut self, timeout: Duration) -> Result<(), ProviderErrors> {
+        run_with_deadline(
+            timeout,
+            self.tracer_provider.take(),
+            self.meter_provider.take(),
+            move |tracer_provider| tracer_provider.shutdown_with_timeout(timeout),
+            move |meter_provider| meter_provider.shutdown_with_timeout(timeout),
+        )
+    }
+}
+
+// Run the operation of each provider on its own thread, reporting those which fail or are
+// still running at the deadline
+fn run_with_deadline(
+    timeout: Duration,
+    tracer_provider: Option<SdkTracerProvider>,
+    meter_provider: Option<SdkMeterProvider>,
+    tracer_op: impl FnOnce(SdkTracerProvider) -> OTelSdkResult + Send + 'static,
+    meter_op: impl FnOnce(SdkMeterProvider) -> OTelSdkResult + Send + 'static,
+) -> Result<(), ProviderErrors> {
+    let deadline = Instant::now() + timeout;
+    let (sender, receiver) = mpsc::channel();
+    let mut pending = Vec::new();
+    if let Some(tracer_provider) = tracer_provider {
+        let sender = sender.clone();
+        pending.push(Provider::Tracer);
+        thread::spawn(move || {
+            let _ = sender.send((Provider::Tracer, tracer_op(tracer_provider)));
+        });
     }
+    if let Some(meter_provider) = meter_provider {
+        pending.push(Provider::Meter);
+        thread::spawn(move || {
+            let _ = sender.send((Provider::Meter, meter_op(meter_provider)));
+        });
+    }
+
+    let mut errors = Vec::new();
+    while !pending.is_empty() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let Ok((provider, result)) = receiver.recv_timeout(remaining) else {
+            break;
+        };
+        pending.retain(|pending| *pending != provider);
+        if let Err(error) = result {
+            errors.push(ProviderError { provider, error });
+        }
+    }
+    errors.extend(pending.into_iter().map(|provider| ProviderError {
+        provider,
+        error: OTelSdkError::Timeout(timeout),
+    }));
+    errors.sort_by_key(|error| error.provider != Provider::Tracer);
+    ProviderErrors::check(errors)
 }
 
 // Drop the guard and shutdown the providers
 impl Drop for OtelGuard {
     fn drop(&mut self) {
-        if let Some(tracer_provider) = self.tracer_provider.take() {
-            if let Err(err) = tracer_provider.shutdown() {
-                eprintln!("{err:?}");
-            }
+        if let Err(err) = self.shutdown_providers(self.shutdown_timeout) {
+            tracing::error!("Failed to shut down OpenTelemetry: {err}");
         }
-        if let Some(meter_provider) = self.meter_provider.take() {
-            if let Err(err) = meter_provider.shutdown() {
-                eprintln!("{err:?}");
-            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::Context;
+    use opentelemetry_sdk::{
+        metrics::{InMemoryMetricExporter, PeriodicReader},
+        trace::{Span, SpanData, SpanProcessor},
+    };
+
+    // A processor whose flush and shutdown hang, like one waiting on a collector which is down
+    #[derive(Debug)]
+    struct HangingProcessor;
+
+    impl SpanProcessor for HangingProcessor {
+        fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+        fn on_end(&self, _span: SpanData) {}
+
+        fn force_flush(&self) -> OTelSdkResult {
+            thread::sleep(Duration::from_secs(5));
+            Ok(())
         }
+
+        fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+            thread::sleep(Duration::from_secs(5));
+            Ok(())
+        }
+    }
+
+    fn meter_provider() -> SdkMeterProvider {
+        let reader = PeriodicReader::builder(InMemoryMetricExporter::default()).build();
+        SdkMeterProvider::builder().with_reader(reader).build()
+    }
+
+    #[test]
+    fn test_shutdown_with_timeout() {
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_span_processor(HangingProcessor)
+            .build();
+        let guard = OtelGuard::new(Some(tracer_provider), Some(meter_provider()));
+
+        let started = Instant::now();
+        let errors = guard
+            .force_flush_with_timeout(Duration::from_millis(100))
+            .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].provider, Provider::Tracer);
+
+        let started = Instant::now();
+        let errors = guard
+            .shutdown_with_timeout(Duration::from_millis(100))
+            .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].provider, Provider::Tracer);
+        assert!(matches!(errors.errors[0].error, OTelSdkError::Timeout(_)));
+        assert_eq!(
+            errors.to_string(),
+            "tracer provider: Operation timed out after 100ms"
+        );
+    }
+
+    #[test]
+    fn test_drop_with_shutdown_timeout() {
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_span_processor(HangingProcessor)
+            .build();
+        let guard = OtelGuard::new(Some(tracer_provider), None)
+            .with_shutdown_timeout(Duration::from_millis(100));
+
+        let started = Instant::now();
+        drop(guard);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[cfg(feature = "shutdown")]
+    #[tokio::test]
+    async fn test_shutdown() {
+        let guard = OtelGuard::new(None, Some(meter_provider()));
+        guard.shutdown().await.unwrap();
     }
 }
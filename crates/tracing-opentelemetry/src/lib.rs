@@ -11,6 +11,7 @@
 //!
 //! - Easy OpenTelemetry initialization with OTLP exporter
 //! - Configurable sampling and resource attributes
+//! - Opt-in host, OS, process, container and Kubernetes resource detectors
 //! - Automatic cleanup with guard pattern
 //! - Support for both tracing and metrics
 //!
@@ -52,6 +53,7 @@
 //! }
 //! ```
 
+mod detectors;
 mod guard;
 mod otel;
 mod resource;
@@ -59,11 +61,20 @@ mod resource;
 mod subscriber;
 
 // Re-exports
-pub use guard::OtelGuard;
-pub use otel::{init_meter_provider, init_tracer_provider};
-pub use resource::get_resource;
+pub use detectors::{
+    ContainerDetector, Detector, HostDetector, K8sDetector, OsDetector, ProcessDetector,
+};
+pub use guard::{OtelGuard, Provider, ProviderError, ProviderErrors, DEFAULT_SHUTDOWN_TIMEOUT};
+pub use otel::{
+    build_meter_provider, build_tracer_provider, build_tracer_provider_with_sampler,
+    init_meter_provider, init_tracer_provider,
+};
+pub use resource::{get_resource, get_resource_with_detectors};
 #[cfg(feature = "subscriber")]
-pub use subscriber::{init_env_filter, init_tracing_subscriber, BoxLayer};
+pub use subscriber::{
+    build_subscriber, init_env_filter, init_subscriber, init_tracing_subscriber,
+    try_init_subscriber, BoxLayer, TracingSubscriber,
+};
 
 // Re-exports opentelemetry crates
 pub use opentelemetry;
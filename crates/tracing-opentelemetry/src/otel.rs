@@ -17,6 +17,31 @@ use opentelemetry_sdk::{
 
 /// Initializes a tracer provider for OpenTelemetry tracing.
 ///
+/// The provider is built by [`build_tracer_provider`], then registered as the global tracer
+/// provider, with the W3C trace context as the global propagator.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_opentelemetry_extra::{get_resource, init_tracer_provider};
+/// use opentelemetry::KeyValue;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let resource = get_resource("my-service", &[]);
+///     let tracer_provider = init_tracer_provider(&resource, 1.0)?;
+///     Ok(())
+/// }
+/// ```
+pub fn init_tracer_provider(resource: &Resource, sample_ratio: f64) -> Result<SdkTracerProvider> {
+    let tracer_provider = build_tracer_provider(resource, sample_ratio)?;
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(tracer_provider.clone());
+    Ok(tracer_provider)
+}
+
+/// Builds a tracer provider for OpenTelemetry tracing, without registering it globally.
+///
 /// This function sets up a tracer provider with the following features:
 /// - Parent-based sampling
 /// - Random ID generation
@@ -36,40 +61,87 @@ use opentelemetry_sdk::{
 /// # Examples
 ///
 /// ```rust
-/// use tracing_opentelemetry_extra::{get_resource, init_tracer_provider};
-/// use opentelemetry::KeyValue;
+/// use tracing_opentelemetry_extra::{build_tracer_provider, get_resource};
 ///
 /// #[tokio::main]
 /// async fn main() -> anyhow::Result<()> {
 ///     let resource = get_resource("my-service", &[]);
-///     let tracer_provider = init_tracer_provider(&resource, 1.0)?;
+///     let tracer_provider = build_tracer_provider(&resource, 1.0)?;
 ///     Ok(())
 /// }
 /// ```
-pub fn init_tracer_provider(resource: &Resource, sample_ratio: f64) -> Result<SdkTracerProvider> {
-    global::set_text_map_propagator(TraceContextPropagator::new());
+pub fn build_tracer_provider(resource: &Resource, sample_ratio: f64) -> Result<SdkTracerProvider> {
+    build_tracer_provider_with_sampler(
+        resource,
+        Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(sample_ratio))),
+    )
+}
 
+/// Builds a tracer provider like [`build_tracer_provider`], with the given sampler.
+///
+/// # Examples
+///
+/// ```rust
+/// use opentelemetry_sdk::trace::Sampler;
+/// use tracing_opentelemetry_extra::{build_tracer_provider_with_sampler, get_resource};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let resource = get_resource("my-service", &[]);
+///     // Sample a quarter of the traces, whatever the decision of the parent span
+///     let tracer_provider =
+///         build_tracer_provider_with_sampler(&resource, Sampler::TraceIdRatioBased(0.25))?;
+///     Ok(())
+/// }
+/// ```
+pub fn build_tracer_provider_with_sampler(
+    resource: &Resource,
+    sampler: Sampler,
+) -> Result<SdkTracerProvider> {
     let exporter = opentelemetry_otlp::SpanExporter::builder()
         .with_tonic()
         .build()
         .context("Failed to build OTLP exporter")?;
 
     let tracer_provider = SdkTracerProvider::builder()
-        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
-            sample_ratio,
-        ))))
+        .with_sampler(sampler)
         .with_id_generator(RandomIdGenerator::default())
         .with_resource(resource.clone())
         .with_batch_exporter(exporter)
         .build();
 
-    global::set_tracer_provider(tracer_provider.clone());
-
     Ok(tracer_provider)
 }
 
 /// Initializes a meter provider for OpenTelemetry metrics.
 ///
+/// The provider is built by [`build_meter_provider`], then registered as the global meter
+/// provider.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_opentelemetry_extra::{get_resource, init_meter_provider};
+/// use opentelemetry::KeyValue;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let resource = get_resource("my-service", &[]);
+///     let meter_provider = init_meter_provider(&resource, 30)?;
+///     Ok(())
+/// }
+/// ```
+pub fn init_meter_provider(
+    resource: &Resource,
+    metrics_interval_secs: u64,
+) -> Result<SdkMeterProvider> {
+    let meter_provider = build_meter_provider(resource, metrics_interval_secs)?;
+    global::set_meter_provider(meter_provider.clone());
+    Ok(meter_provider)
+}
+
+/// Builds a meter provider for OpenTelemetry metrics, without registering it globally.
+///
 /// This function sets up a meter provider with the following features:
 /// - Periodic metric collection
 /// - OTLP exporter
@@ -88,17 +160,16 @@ pub fn init_tracer_provider(resource: &Resource, sample_ratio: f64) -> Result<Sd
 /// # Examples
 ///
 /// ```rust
-/// use tracing_opentelemetry_extra::{get_resource, init_meter_provider};
-/// use opentelemetry::KeyValue;
+/// use tracing_opentelemetry_extra::{build_meter_provider, get_resource};
 ///
 /// #[tokio::main]
 /// async fn main() -> anyhow::Result<()> {
 ///     let resource = get_resource("my-service", &[]);
-///     let meter_provider = init_meter_provider(&resource, 30)?;
+///     let meter_provider = build_meter_provider(&resource, 30)?;
 ///     Ok(())
 /// }
 /// ```
-pub fn init_meter_provider(
+pub fn build_meter_provider(
     resource: &Resource,
     metrics_interval_secs: u64,
 ) -> Result<SdkMeterProvider> {
@@ -117,7 +188,6 @@ pub fn init_meter_provider(
         .with_reader(reader);
 
     let meter_provider = meter_builder.build();
-    global::set_meter_provider(meter_provider.clone());
 
     Ok(meter_provider)
 }
@@ -1,5 +1,9 @@
+use crate::detectors::Detector;
 use opentelemetry::KeyValue;
-use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::{
+    resource::{EnvResourceDetector, SdkProvidedResourceDetector, TelemetryResourceDetector},
+    Resource,
+};
 
 /// Creates a resource with the given service name and attributes.
 ///
@@ -23,8 +27,64 @@ use opentelemetry_sdk::Resource;
 /// );
 /// ```
 pub fn get_resource(service_name: &str, attributes: &[KeyValue]) -> Resource {
-    Resource::builder()
+    get_resource_with_detectors(service_name, attributes, &[])
+}
+
+/// Creates a resource like [`get_resource`], with the attributes of the given detectors.
+///
+/// Attributes are merged in this order, a later source taking precedence for the same key:
+///
+/// 1. The detectors, in the given order
+/// 2. The SDK defaults and `OTEL_RESOURCE_ATTRIBUTES`
+/// 3. The service name
+/// 4. The explicit `attributes`
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_opentelemetry_extra::{get_resource_with_detectors, Detector};
+///
+/// let resource = get_resource_with_detectors(
+///     "my-service",
+///     &[],
+///     &[Detector::Host, Detector::Process],
+/// );
+/// ```
+pub fn get_resource_with_detectors(
+    service_name: &str,
+    attributes: &[KeyValue],
+    detectors: &[Detector],
+) -> Resource {
+    let detectors: Vec<_> = detectors.iter().map(Detector::detector).collect();
+    Resource::builder_empty()
+        .with_detectors(&detectors)
+        .with_detectors(&[
+            Box::new(SdkProvidedResourceDetector),
+            Box::new(TelemetryResourceDetector),
+            Box::new(EnvResourceDetector::new()),
+        ])
         .with_service_name(service_name.to_string())
         .with_attributes(attributes.to_vec())
         .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::{Key, Value};
+
+    #[test]
+    fn test_explicit_attributes_take_precedence() {
+        let resource = get_resource_with_detectors(
+            "my-service",
+            &[KeyValue::new("process.pid", 1)],
+            &[Detector::Process, Detector::Os],
+        );
+        assert_eq!(
+            resource.get(&Key::new("service.name")),
+            Some(Value::from("my-service"))
+        );
+        assert_eq!(resource.get(&Key::new("process.pid")), Some(Value::I64(1)));
+        assert!(resource.get(&Key::new("os.type")).is_some());
+    }
+}
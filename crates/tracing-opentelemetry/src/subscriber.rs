@@ -1,9 +1,11 @@
 use crate::guard::OtelGuard;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use opentelemetry_sdk::{metrics::SdkMeterProvider, trace::SdkTracerProvider};
 use tracing::Level;
 use tracing_subscriber::{
-    layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+    layer::{Layered, SubscriberExt},
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
 };
 
 pub type BoxLayer = Box<dyn Layer<Registry> + Sync + Send>;
@@ -34,23 +36,83 @@ pub fn init_env_filter(level: &Level) -> EnvFilter {
 pub fn init_tracing_subscriber(
     name: &str,
     env_filter: EnvFilter,
-    mut layers: Vec<BoxLayer>,
+    layers: Vec<BoxLayer>,
     tracer_provider: SdkTracerProvider,
     meter_provider: SdkMeterProvider,
 ) -> Result<OtelGuard> {
-    use opentelemetry::trace::TracerProvider as _;
-    // Set up telemetry layer with tracer
-    let tracer = tracer_provider.tracer(name.to_string());
-    let metrics_layer = tracing_opentelemetry::MetricsLayer::new(meter_provider.clone());
-    let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
+    init_subscriber(
+        name,
+        env_filter,
+        layers,
+        Some(tracer_provider),
+        Some(meter_provider),
+    )
+}
+
+/// Initializes tracing-subscriber with the given providers, each of which may be omitted.
+///
+/// The OpenTelemetry layer is only added with a tracer provider, and the
+/// metrics layer only with a meter provider. Without either, only `layers` are installed and
+/// the returned guard holds no provider.
+///
+/// # Panics
+///
+/// Panics if a global default subscriber has already been set, see [`try_init_subscriber`].
+pub fn init_subscriber(
+    name: &str,
+    env_filter: EnvFilter,
+    layers: Vec<BoxLayer>,
+    tracer_provider: Option<SdkTracerProvider>,
+    meter_provider: Option<SdkMeterProvider>,
+) -> Result<OtelGuard> {
+    let (subscriber, guard) =
+        build_subscriber(name, env_filter, layers, tracer_provider, meter_provider);
+    subscriber.init();
+    Ok(guard)
+}
 
-    let extended_layers: Vec<BoxLayer> = vec![Box::new(metrics_layer), Box::new(otel_layer)];
+/// Like [`init_subscriber`], but returns an error instead of panicking if a global default
+/// subscriber has already been set. The providers are then shut down.
+pub fn try_init_subscriber(
+    name: &str,
+    env_filter: EnvFilter,
+    layers: Vec<BoxLayer>,
+    tracer_provider: Option<SdkTracerProvider>,
+    meter_provider: Option<SdkMeterProvider>,
+) -> Result<OtelGuard> {
+    let (subscriber, guard) =
+        build_subscriber(name, env_filter, layers, tracer_provider, meter_provider);
+    subscriber
+        .try_init()
+        .context("Failed to set the global default subscriber")?;
+    Ok(guard)
+}
+
+/// The subscriber composed by [`build_subscriber`].
+pub type TracingSubscriber = Layered<EnvFilter, Layered<Vec<BoxLayer>, Registry>>;
+
+/// Composes the subscriber of [`init_subscriber`] without installing it, for use with
+/// [`tracing::subscriber::with_default`] or [`tracing::subscriber::set_default`].
+pub fn build_subscriber(
+    name: &str,
+    env_filter: EnvFilter,
+    mut layers: Vec<BoxLayer>,
+    tracer_provider: Option<SdkTracerProvider>,
+    meter_provider: Option<SdkMeterProvider>,
+) -> (TracingSubscriber, OtelGuard) {
+    use opentelemetry::trace::TracerProvider as _;
 
-    layers.extend(extended_layers);
+    if let Some(meter_provider) = &meter_provider {
+        let metrics_layer = tracing_opentelemetry::MetricsLayer::new(meter_provider.clone());
+        layers.push(Box::new(metrics_layer));
+    }
+    if let Some(tracer_provider) = &tracer_provider {
+        // Set up telemetry layer with tracer
+        let tracer = tracer_provider.tracer(name.to_string());
+        let otel_layer = tracing_opentelemetry::OpenTelemetryLayer::new(tracer);
+        layers.push(Box::new(otel_layer));
+    }
 
-    tracing_subscriber::registry()
-        .with(layers)
-        .with(env_filter)
-        .init();
-    Ok(OtelGuard::new(Some(tracer_provider), Some(meter_provider)))
+    let subscriber = tracing_subscriber::registry().with(layers).with(env_filter);
+    (subscriber, OtelGuard::new(tracer_provider, meter_provider))
 }
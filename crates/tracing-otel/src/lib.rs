@@ -9,6 +9,9 @@
 //! - `otel`: OpenTelemetry integration for distributed tracing
 //! - `logger`: Basic logging functionality with configurable formats
 //! - `env`: Environment-based logging configuration
+//! - `config`: Logging configuration from TOML, YAML or JSON files
+//! - `shutdown`: Graceful shutdown of a server and its telemetry on `SIGINT` or `SIGTERM`,
+//!   and the async `LoggerGuard::shutdown`
 //! - `context`: Trace context utilities
 //! - `fields`: Common tracing fields and attributes
 //! - `http`: HTTP request/response tracing
@@ -83,12 +86,18 @@ pub use otel::*;
 
 // Logger module exports
 #[cfg(feature = "logger")]
-pub use logs::{init_logging, FmtSpan, LogFormat, LogRollingRotation, Logger, LoggerFileAppender};
+pub use logs::{
+    init_logging, FmtSpan, LogCompression, LogFormat, LogRollingRotation, Logger,
+    LoggerFileAppender, LoggerGuard,
+};
 
 // Logger module exports
 #[cfg(feature = "env")]
 pub use logs::{init_logger_from_env, init_logging_from_env};
 
+#[cfg(feature = "shutdown")]
+pub use logs::GracefulShutdown;
+
 // Macros module exports
 #[cfg(feature = "macros")]
 pub mod macros;
@@ -0,0 +1,395 @@
+//! Typed resource attributes, as set with `LOG_ATTRIBUTES` or `attributes` in a
+//! configuration file.
+//!
+//! Attributes are comma separated `key=value` pairs. The type of a value is detected, or
+//! given with a `:type` suffix on the key:
+//!
+//! | Example | Value |
+//! |---------|-------|
+//! | `team=payments` | `"payments"` |
+//! | `replicas=3` | `3` |
+//! | `ratio=0.5` | `0.5` |
+//! | `canary=true` | `true` |
+//! | `zones=[a,b]` | `["a", "b"]` |
+//! | `ports=[80,443]` | `[80, 443]` |
+//! | `build:string=0042` | `"0042"` |
+//! | `weight:float=1` | `1.0` |
+//! | `ids:int=[1,2]` | `[1, 2]` |
+//!
+//! The types are `string`, `int`, `float` and `bool`. Integers, floats and booleans are only
+//! detected when they are written exactly as they would be printed, so `007`, `1.0` and
+//! `1e3` stay strings. An array is detected as a single type for all of its elements, or
+//! strings otherwise.
+//!
+//! Keys and values are percent-decoded, as in `OTEL_RESOURCE_ATTRIBUTES`, and a backslash
+//! escapes a following `,`, `=`, `:`, `[` or `]`, so `a%2Cb` and `a\,b` are both `a,b`.
+//! Any other backslash, and a `%` which is not followed by two hex digits, is kept as is,
+//! so `dir=C:\logs` and `threshold=50%` need no escaping.
+
+use opentelemetry::{Array, KeyValue, StringValue, Value};
+
+/// Parse comma separated `key=value` pairs into typed attributes.
+pub fn parse_attributes(s: &str) -> Result<Vec<KeyValue>, String> {
+    split_unescaped(s, ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(parse_attribute)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueType {
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl ValueType {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "string" => Some(ValueType::String),
+            "int" => Some(ValueType::Int),
+            "float" => Some(ValueType::Float),
+            "bool" => Some(ValueType::Bool),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ValueType::String => "string",
+            ValueType::Int => "int",
+            ValueType::Float => "float",
+            ValueType::Bool => "bool",
+        }
+    }
+
+    // The type all values are written in exactly as they would be printed
+    fn detect(values: &[String]) -> Self {
+        let detected = |value_type: fn(&str) -> bool| values.iter().all(|value| value_type(value));
+        if values.is_empty() {
+            ValueType::String
+        } else if detected(|value| value == "true" || value == "false") {
+            ValueType::Bool
+        } else if detected(|value| value.parse::<i64>().is_ok_and(|n| n.to_string() == value)) {
+            ValueType::Int
+        } else if detected(|value| {
+            value
+                .parse::<f64>()
+                .is_ok_and(|n| n.is_finite() && n.to_string() == value)
+        }) {
+            ValueType::Float
+        } else {
+            ValueType::String
+        }
+    }
+}
+
+fn parse_attribute(pair: &str) -> Result<KeyValue, String> {
+    let (key, value) = split_unescaped(pair, '=')
+        .first()
+        .filter(|key| key.len() < pair.len())
+        .map(|key| (key.trim(), pair[key.len() + 1..].trim()))
+        .ok_or_else(|| format!("Invalid attribute: '{pair}'"))?;
+    if key.is_empty() || value.is_empty() {
+        return Err(format!("Empty key or value: '{pair}'"));
+    }
+
+    let (key, value_type) = match key.rsplit_once(':') {
+        Some((name, suffix)) if !ends_with_escape(name) => match ValueType::parse(suffix) {
+            Some(value_type) => (name.trim(), Some(value_type)),
+            None => (key, None),
+        },
+        _ => (key, None),
+    };
+    let key = unescape(key).ok_or_else(|| format!("Invalid escape in attribute: '{pair}'"))?;
+    let invalid = |value_type: ValueType, value: &str| {
+        format!(
+            "Invalid {} value for attribute '{key}': '{value}'",
+            value_type.name()
+        )
+    };
+
+    let value = match value
+        .strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+        .filter(|inner| !ends_with_escape(inner))
+    {
+        Some(inner) => {
+            let values = split_unescaped(inner, ',')
+                .into_iter()
+                .map(str::trim)
+                .filter(|_| !inner.trim().is_empty())
+                .map(|value| {
+                    if value.is_empty() || value.starts_with('[') {
+                        return Err(format!("Invalid array in attribute: '{pair}'"));
+                    }
+                    unescape(value).ok_or_else(|| format!("Invalid escape in attribute: '{pair}'"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let value_type = value_type.unwrap_or_else(|| ValueType::detect(&values));
+            let array = match value_type {
+                ValueType::String => {
+                    Array::String(values.into_iter().map(StringValue::from).collect())
+                }
+                ValueType::Int => Array::I64(
+                    values
+                        .iter()
+                        .map(|value| parse_int(value).ok_or_else(|| invalid(value_type, value)))
+                        .collect::<Result<_, _>>()?,
+                ),
+                ValueType::Float => Array::F64(
+                    values
+                        .iter()
+                        .map(|value| parse_float(value).ok_or_else(|| invalid(value_type, value)))
+                        .collect::<Result<_, _>>()?,
+                ),
+                ValueType::Bool => Array::Bool(
+                    values
+                        .iter()
+                        .map(|value| parse_bool(value).ok_or_else(|| invalid(value_type, value)))
+                        .collect::<Result<_, _>>()?,
+                ),
+            };
+            Value::Array(array)
+        }
+        None => {
+            let value =
+                unescape(value).ok_or_else(|| format!("Invalid escape in attribute: '{pair}'"))?;
+            let value_type =
+                value_type.unwrap_or_else(|| ValueType::detect(std::slice::from_ref(&value)));
+            match value_type {
+                ValueType::String => Value::from(value),
+                ValueType::Int => parse_int(&value)
+                    .map(Value::I64)
+                    .ok_or_else(|| invalid(value_type, &value))?,
+                ValueType::Float => parse_float(&value)
+                    .map(Value::F64)
+                    .ok_or_else(|| invalid(value_type, &value))?,
+                ValueType::Bool => parse_bool(&value)
+                    .map(Value::Bool)
+                    .ok_or_else(|| invalid(value_type, &value))?,
+            }
+        }
+    };
+    Ok(KeyValue::new(key, value))
+}
+
+fn parse_int(value: &str) -> Option<i64> {
+    value.trim().parse().ok()
+}
+
+fn parse_float(value: &str) -> Option<f64> {
+    value.trim().parse().ok()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+// Split on the separator outside of escapes and brackets
+fn split_unescaped(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut chars = s.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if chars.clone().next().is_some_and(|(_, c)| is_escapable(c)) => {
+                chars.next();
+            }
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            c if c == separator && depth == 0 => {
+                parts.push(&s[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+// The characters a backslash escapes, any other backslash is kept as is
+fn is_escapable(c: char) -> bool {
+    matches!(c, ',' | '=' | ':' | '[' | ']')
+}
+
+// Whether the string ends with a backslash escaping the character which follows it
+fn ends_with_escape(s: &str) -> bool {
+    s.ends_with('\\')
+}
+
+// Resolve backslash escapes and percent-encoding, keeping a malformed `%XX` escape as is.
+// Returns `None` for invalid UTF-8.
+fn unescape(s: &str) -> Option<String> {
+    decode(s, true)
+}
+
+/// Decode `%XX` escapes. Returns `None` for a malformed escape or invalid UTF-8.
+pub(crate) fn percent_decode(s: &str) -> Option<String> {
+    decode(s, false)
+}
+
+fn decode(s: &str, lenient: bool) -> Option<String> {
+    let input = s.as_bytes();
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut index = 0;
+    while index < input.len() {
+        match input[index] {
+            b'\\'
+                if lenient
+                    && input
+                        .get(index + 1)
+                        .is_some_and(|c| is_escapable(*c as char)) =>
+            {
+                bytes.push(input[index + 1]);
+                index += 2;
+            }
+            b'%' => match input.get(index + 1..index + 3).and_then(hex_byte) {
+                Some(byte) => {
+                    bytes.push(byte);
+                    index += 3;
+                }
+                None if lenient => {
+                    bytes.push(b'%');
+                    index += 1;
+                }
+                None => return None,
+            },
+            byte => {
+                bytes.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn hex_byte(hex: &[u8]) -> Option<u8> {
+    if !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Value {
+        Value::Array(Array::String(
+            values
+                .iter()
+                .map(|value| value.to_string().into())
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_detected_types() {
+        assert_eq!(
+            parse_attributes("team=payments,replicas=3,ratio=0.5,canary=true,version=1.0,zip=007")
+                .unwrap(),
+            [
+                KeyValue::new("team", "payments"),
+                KeyValue::new("replicas", 3),
+                KeyValue::new("ratio", 0.5),
+                KeyValue::new("canary", true),
+                KeyValue::new("version", "1.0"),
+                KeyValue::new("zip", "007"),
+            ]
+        );
+        assert_eq!(
+            parse_attributes("zones=[a, b],ports=[80,443],mixed=[1,x],empty=[]").unwrap(),
+            [
+                KeyValue::new("zones", strings(&["a", "b"])),
+                KeyValue::new("ports", Value::Array(vec![80, 443].into())),
+                KeyValue::new("mixed", strings(&["1", "x"])),
+                KeyValue::new("empty", Value::Array(Array::String(vec![]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_suffix() {
+        assert_eq!(
+            parse_attributes("build:string=0042,weight:float=1,ids:int=[1,2],on:bool=TRUE")
+                .unwrap(),
+            [
+                KeyValue::new("build", "0042"),
+                KeyValue::new("weight", 1.0),
+                KeyValue::new("ids", Value::Array(vec![1, 2].into())),
+                KeyValue::new("on", true),
+            ]
+        );
+        // an unknown suffix is part of the key
+        assert_eq!(
+            parse_attributes("url:path=/").unwrap(),
+            [KeyValue::new("url:path", "/")]
+        );
+        assert_eq!(
+            parse_attributes("replicas:int=three").unwrap_err(),
+            "Invalid int value for attribute 'replicas': 'three'"
+        );
+        assert_eq!(
+            parse_attributes("ports:int=[80,http]").unwrap_err(),
+            "Invalid int value for attribute 'ports': 'http'"
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(
+            parse_attributes(r"query=a\,b\=c,path=a%2Cb%3Dc,list=\[1\],key\:int=5").unwrap(),
+            [
+                KeyValue::new("query", "a,b=c"),
+                KeyValue::new("path", "a,b=c"),
+                KeyValue::new("list", "[1]"),
+                KeyValue::new("key:int", 5),
+            ]
+        );
+        assert_eq!(
+            parse_attributes("tags=[a\\,b,c]").unwrap(),
+            [KeyValue::new("tags", strings(&["a,b", "c"]))]
+        );
+        assert!(parse_attributes("tags=[[a]]").is_err());
+        assert!(parse_attributes("team=%FF").is_err());
+    }
+
+    #[test]
+    fn test_literal_backslash_and_percent() {
+        assert_eq!(
+            parse_attributes(r"dir=C:\logs,share=\\server\logs,threshold=50%,team=%zz,sign=%+1")
+                .unwrap(),
+            [
+                KeyValue::new("dir", r"C:\logs"),
+                KeyValue::new("share", r"\\server\logs"),
+                KeyValue::new("threshold", "50%"),
+                KeyValue::new("team", "%zz"),
+                KeyValue::new("sign", "%+1"),
+            ]
+        );
+        // a backslash before an escapable character still escapes it
+        assert_eq!(
+            parse_attributes(r"path=C:\dir\,x").unwrap(),
+            [KeyValue::new("path", r"C:\dir,x")]
+        );
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b%2C").as_deref(), Some("a b,"));
+        assert_eq!(percent_decode("caf%C3%A9").as_deref(), Some("café"));
+        assert_eq!(percent_decode("%2"), None);
+        assert_eq!(percent_decode("%+1"), None);
+        assert_eq!(percent_decode("%FF"), None);
+    }
+}
@@ -0,0 +1,438 @@
+//! Logger configuration from TOML, YAML or JSON files.
+//!
+//! The keys of a configuration file are the fields of [`Logger`], with the file appenders,
+//! JSON options, syslog and journald outputs as nested tables:
+//!
+//! ```toml
+//! service_name = "my-service"
+//! level = "info"
+//! format = "json"
+//!
+//! [json]
+//! span_list = false
+//! renames = { message = "msg" }
+//!
+//! [[file_appenders]]
+//! enable = true
+//! name = "error"
+//! filter = "warn"
+//! max_bytes = "100MB"
+//! compression = "gzip"
+//!
+//! [syslog]
+//! enable = true
+//! transport = "udp"
+//! address = "127.0.0.1:514"
+//! ```
+//!
+//! ## Precedence
+//!
+//! From lowest to highest:
+//!
+//! 1. The defaults of [`Logger`]
+//! 2. The configuration file
+//! 3. The standard `OTEL_*` environment variables, see [`otel_env`](crate::logs::otel_env)
+//! 4. The `LOG_` environment variables, with the same names as for [`Logger::from_env`]
+//! 5. Builder methods called on the loaded [`Logger`]
+//!
+//! Environment variables override single keys, so `LOG_FILE_0_LEVEL=debug` changes the level
+//! of the first entry of `file_appenders` and keeps the rest of it. `LOG_FILE_{NAME}_*`
+//! variables override the entry with that `name`, and the names listed in `LOG_FILE_NAMES`
+//! which are not in the file add new entries after the others.
+
+use crate::logs::{otel_env::apply_otel_vars, validate::env_var_warnings, Logger};
+use anyhow::{Context, Result};
+use config::{Config, File, FileFormat};
+use std::path::Path;
+
+/// The format of a logger configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format from the extension of the file: `.toml`, `.yaml`, `.yml` or `.json`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+        match extension.to_ascii_lowercase().as_str() {
+            "toml" => Ok(Self::Toml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "json" => Ok(Self::Json),
+            _ => anyhow::bail!(
+                "Unsupported configuration file '{}', expected a .toml, .yaml, .yml or .json file",
+                path.display()
+            ),
+        }
+    }
+
+    fn file_format(self) -> FileFormat {
+        match self {
+            Self::Toml => FileFormat::Toml,
+            Self::Yaml => FileFormat::Yaml,
+            Self::Json => FileFormat::Json,
+        }
+    }
+}
+
+impl Logger {
+    /// Load the configuration from a TOML, YAML or JSON file, detected from its extension,
+    /// with the environment variables under `env_prefix` (`LOG_` by default) layered on top.
+    ///
+    /// Errors name the offending key, such as `file_appenders[0].rotation`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tracing_otel_extra::Logger;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let _guard = Logger::from_file("logging.toml", None)?.init()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>, env_prefix: Option<&str>) -> Result<Self> {
+        Self::from_file_with_warnings(path, env_prefix).map(|(logger, _)| logger)
+    }
+
+    /// Load the configuration from a file like [`Logger::from_file`], along with the warnings
+    /// about the environment variables it ignored, such as a misspelled `LOG_LEVL`.
+    pub fn from_file_with_warnings(
+        path: impl AsRef<Path>,
+        env_prefix: Option<&str>,
+    ) -> Result<(Self, Vec<String>)> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)?;
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read logger configuration {}", path.display()))?;
+        Self::from_config_with_warnings(&source, format, env_prefix)
+            .with_context(|| format!("Invalid logger configuration {}", path.display()))
+    }
+
+    /// Load the configuration from a string in the given format, with the environment
+    /// variables under `env_prefix` (`LOG_` by default) layered on top.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::{logs::ConfigFormat, Logger};
+    ///
+    /// let logger = Logger::from_config(
+    ///     r#"
+    ///     service_name = "my-service"
+    ///     level = "debug"
+    ///     "#,
+    ///     ConfigFormat::Toml,
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn from_config(
+        source: &str,
+        format: ConfigFormat,
+        env_prefix: Option<&str>,
+    ) -> Result<Self> {
+        Self::from_config_with_warnings(source, format, env_prefix).map(|(logger, _)| logger)
+    }
+
+    /// Load the configuration from a string like [`Logger::from_config`], along with the
+    /// warnings about the environment variables it ignored.
+    pub fn from_config_with_warnings(
+        source: &str,
+        format: ConfigFormat,
+        env_prefix: Option<&str>,
+    ) -> Result<(Self, Vec<String>)> {
+        let vars: Vec<(String, String)> = std::env::vars().collect();
+        logger_from_config(source, format, env_prefix.unwrap_or("LOG_"), &vars)
+    }
+}
+
+fn logger_from_config(
+    source: &str,
+    format: ConfigFormat,
+    prefix: &str,
+    vars: &[(String, String)],
+) -> Result<(Logger, Vec<String>)> {
+    let base = Config::builder()
+        .add_source(File::from_str(source, format.file_format()))
+        .build()
+        .context("Failed to parse logger configuration")?;
+
+    let names = file_appender_names(&base);
+    let mut builder = Config::builder().add_source(base);
+    for (key, value) in env_overrides(prefix, vars, names) {
+        builder = builder
+            .set_override(&key, value)
+            .with_context(|| format!("Invalid environment override for key `{key}`"))?;
+    }
+    let mut logger: Logger = builder
+        .build()
+        .and_then(Config::try_deserialize)
+        .context("Failed to deserialize logger configuration")?;
+    let mut warnings = Vec::new();
+    apply_otel_vars(&mut logger, prefix, vars, &mut warnings);
+
+    // Fill the unset options of the file appenders from the logger, as `from_env` does
+    if let Some(file_appender) = logger.file_appender.take() {
+        logger.file_appender = Some(file_appender.merge_with_logger(&logger));
+    }
+    logger.file_appenders = logger
+        .file_appenders
+        .iter()
+        .map(|file_appender| file_appender.merge_with_logger(&logger))
+        .collect();
+    warnings.extend(env_var_warnings(prefix, vars));
+    logger.validate()?;
+    Ok((logger, warnings))
+}
+
+// The names of the `file_appenders` entries of the configuration, by index
+fn file_appender_names(config: &Config) -> Vec<Option<String>> {
+    config
+        .get_array("file_appenders")
+        .unwrap_or_default()
+        .into_iter()
+        .map(|value| {
+            let name = value.into_table().ok()?.remove("name")?;
+            name.into_string().ok()
+        })
+        .collect()
+}
+
+/// Map the `{prefix}*` environment variables to configuration keys, following the names
+/// used by [`Logger::from_env`].
+fn env_overrides(
+    prefix: &str,
+    vars: &[(String, String)],
+    mut names: Vec<Option<String>>,
+) -> Vec<(String, String)> {
+    let file_prefix = format!("{prefix}FILE_");
+    let mut overrides = Vec::new();
+
+    // Indexed appenders may extend the list, named ones not in the file are added after them
+    let next_index = vars
+        .iter()
+        .filter_map(|(key, _)| appender_index(key.strip_prefix(&file_prefix)?))
+        .map(|(index, _)| index + 1)
+        .fold(names.len(), usize::max);
+    names.resize(next_index, None);
+    let new_names = vars
+        .iter()
+        .filter(|(key, _)| key.strip_prefix(&file_prefix) == Some("NAMES"))
+        .flat_map(|(_, names)| names.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    for name in new_names {
+        if !names
+            .iter()
+            .flatten()
+            .any(|known| known.eq_ignore_ascii_case(name))
+        {
+            let name = name.to_lowercase();
+            overrides.push((
+                format!("file_appenders[{}].name", names.len()),
+                name.clone(),
+            ));
+            names.push(Some(name));
+        }
+    }
+
+    for (key, value) in vars {
+        let Some(rest) = key.strip_prefix(prefix) else {
+            continue;
+        };
+        if rest.is_empty() || !rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            continue;
+        }
+        let key = if let Some(rest) = rest.strip_prefix("FILE_") {
+            if rest == "NAMES" {
+                continue;
+            }
+            match appender_index(rest).or_else(|| named_appender_index(rest, &names)) {
+                Some((index, field)) => format!("file_appenders[{index}].{}", field_key(field)),
+                None => format!("file_appender.{}", field_key(rest)),
+            }
+        } else if let Some(rest) = rest.strip_prefix("JSON_") {
+            format!("json.{}", rest.to_lowercase())
+        } else if let Some(rest) = rest.strip_prefix("SYSLOG_") {
+            format!("syslog.{}", rest.to_lowercase())
+        } else if let Some(rest) = rest.strip_prefix("JOURNALD_") {
+            format!("journald.{}", rest.to_lowercase())
+        } else {
+            rest.to_lowercase()
+        };
+        overrides.push((key, value.clone()));
+    }
+    overrides
+}
+
+// Split `{index}_{FIELD}` into the index and the field
+fn appender_index(rest: &str) -> Option<(usize, &str)> {
+    let (index, field) = rest.split_once('_')?;
+    Some((index.parse().ok()?, field))
+}
+
+// Split `{NAME}_{FIELD}` into the index of the appender with that name and the field
+fn named_appender_index<'a>(rest: &'a str, names: &[Option<String>]) -> Option<(usize, &'a str)> {
+    names.iter().enumerate().find_map(|(index, name)| {
+        let name = name.as_deref()?.to_uppercase();
+        let field = rest.strip_prefix(&name)?.strip_prefix('_')?;
+        Some((index, field))
+    })
+}
+
+// The key of a file appender field, with its JSON options nested under `json`
+fn field_key(field: &str) -> String {
+    match field.strip_prefix("JSON_") {
+        Some(field) => format!("json.{}", field.to_lowercase()),
+        None => field.to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::{LogCompression, LogFormat, LogRollingRotation};
+    use tracing::Level;
+
+    const TOML: &str = r#"
+service_name = "my-service"
+level = "info"
+format = "json"
+
+[json]
+span_list = false
+renames = { message = "msg" }
+
+[[file_appenders]]
+enable = true
+name = "error"
+filter = "warn"
+max_bytes = "100MB"
+compression = "gzip"
+
+[[file_appenders]]
+enable = true
+name = "audit"
+rotation = "daily"
+"#;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_logger_from_toml() {
+        let (logger, _) = logger_from_config(TOML, ConfigFormat::Toml, "LOG_", &[]).unwrap();
+        assert_eq!(logger.service_name, "my-service");
+        assert_eq!(logger.format, LogFormat::Json);
+        assert!(!logger.json.span_list);
+        assert_eq!(
+            logger.json.renames,
+            [("message".to_string(), "msg".to_string())]
+        );
+        assert_eq!(logger.file_appenders.len(), 2);
+        let error = &logger.file_appenders[0];
+        assert_eq!(error.filter.as_deref(), Some("warn"));
+        assert_eq!(error.max_bytes, Some(100 * 1024 * 1024));
+        assert_eq!(error.compression, LogCompression::Gzip);
+        // Unset options are filled from the logger, except the level left to the global filter
+        assert_eq!(error.level, None);
+        assert_eq!(error.format, Some(LogFormat::Json));
+        assert_eq!(logger.file_appenders[1].rotation, LogRollingRotation::Daily);
+    }
+
+    #[test]
+    fn test_logger_from_yaml_and_json() {
+        let yaml = "
+service_name: my-service
+console_enabled: false
+file_appender:
+  enable: true
+  level: warn
+";
+        let json = r#"{
+            "service_name": "my-service",
+            "console_enabled": false,
+            "file_appender": {"enable": true, "level": "warn"}
+        }"#;
+        for (source, format) in [(yaml, ConfigFormat::Yaml), (json, ConfigFormat::Json)] {
+            let (logger, _) = logger_from_config(source, format, "LOG_", &[]).unwrap();
+            assert_eq!(logger.service_name, "my-service");
+            assert!(!logger.console_enabled);
+            let file_appender = logger.file_appender.unwrap();
+            assert_eq!(file_appender.level, Some(Level::WARN));
+        }
+    }
+
+    #[test]
+    fn test_env_overrides_config() {
+        let vars = vars(&[
+            ("LOG_LEVEL", "debug"),
+            ("LOG_CONSOLE_ENABLED", "false"),
+            ("LOG_JSON_SPAN_LIST", "true"),
+            ("LOG_FILE_0_FILTER", "error"),
+            ("LOG_FILE_AUDIT_JSON_FLATTEN_EVENT", "true"),
+            ("LOG_FILE_NAMES", "audit,access"),
+            ("LOG_FILE_ACCESS_ENABLE", "true"),
+            ("LOG_SYSLOG_ENABLE", "false"),
+            ("OTHER_LEVEL", "error"),
+        ]);
+        let (logger, _) = logger_from_config(TOML, ConfigFormat::Toml, "LOG_", &vars).unwrap();
+        assert_eq!(logger.service_name, "my-service");
+        assert_eq!(logger.level, Level::DEBUG);
+        assert!(!logger.console_enabled);
+        assert!(logger.json.span_list);
+        assert!(!logger.syslog.unwrap().enable);
+
+        let [error, audit, access] = logger.file_appenders.as_slice() else {
+            panic!("unexpected file appenders: {:?}", logger.file_appenders);
+        };
+        assert_eq!(error.filter.as_deref(), Some("error"));
+        assert_eq!(error.max_bytes, Some(100 * 1024 * 1024));
+        assert!(audit.json.as_ref().unwrap().flatten_event);
+        assert_eq!(audit.rotation, LogRollingRotation::Daily);
+        assert_eq!(access.name.as_deref(), Some("access"));
+        assert_eq!(access.level, None);
+    }
+
+    #[test]
+    fn test_config_errors_name_the_key() {
+        let source = "[[file_appenders]]\nenable = true\nrotation = \"weekly\"\n";
+        let err = logger_from_config(source, ConfigFormat::Toml, "LOG_", &[]).unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("file_appenders[0]"), "{message}");
+        assert!(message.contains("rotation"), "{message}");
+
+        let vars = vars(&[("LOG_CONSOLE_ENABLED", "maybe")]);
+        let err = logger_from_config("", ConfigFormat::Toml, "LOG_", &vars).unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("console_enabled"), "{message}");
+    }
+
+    #[test]
+    fn test_config_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path("logging.toml").unwrap(),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_path("/etc/app/logging.YML").unwrap(),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path("logging.json").unwrap(),
+            ConfigFormat::Json
+        );
+        assert!(ConfigFormat::from_path("logging.ini").is_err());
+    }
+}
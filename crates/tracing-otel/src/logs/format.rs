@@ -0,0 +1,642 @@
+use crate::{
+    logs::{JsonOptions, JsonSchema, TimestampFormat},
+    otel::{
+        opentelemetry::trace::{SamplingDecision, SpanId, TraceContextExt, TraceFlags, TraceId},
+        tracing_opentelemetry::OtelData,
+    },
+};
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::value::{to_raw_value, RawValue};
+use std::fmt;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::{
+    fmt::{
+        format::{Compact, Format, Json, Pretty, Writer},
+        FmtContext, FormatEvent, FormatFields,
+    },
+    registry::{LookupSpan, SpanRef},
+};
+
+/// The field name of the OpenTelemetry trace id.
+pub const TRACE_ID_FIELD: &str = "trace_id";
+/// The field name of the OpenTelemetry span id.
+pub const SPAN_ID_FIELD: &str = "span_id";
+/// The field name of the OpenTelemetry trace flags.
+pub const TRACE_FLAGS_FIELD: &str = "trace_flags";
+
+/// The OpenTelemetry identifiers of a `tracing` span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OtelIds {
+    pub trace_id: TraceId,
+    pub span_id: SpanId,
+    pub trace_flags: TraceFlags,
+}
+
+impl OtelIds {
+    /// Returns the ids assigned to the span by the `tracing-opentelemetry` layer.
+    ///
+    /// Returns `None` if the span is not tracked by that layer.
+    pub fn from_span<R>(span: &SpanRef<'_, R>) -> Option<Self>
+    where
+        R: for<'a> LookupSpan<'a>,
+    {
+        let extensions = span.extensions();
+        let otel_data = extensions.get::<OtelData>()?;
+        let parent_span = otel_data.parent_cx.span();
+        let parent = parent_span.span_context();
+
+        let trace_id = if parent.is_valid() {
+            parent.trace_id()
+        } else {
+            otel_data.builder.trace_id?
+        };
+        let span_id = otel_data.builder.span_id?;
+        let trace_flags = match &otel_data.builder.sampling_result {
+            Some(result) if result.decision == SamplingDecision::RecordAndSample => {
+                TraceFlags::SAMPLED
+            }
+            Some(_) => TraceFlags::default(),
+            None => parent.trace_flags(),
+        };
+
+        (trace_id != TraceId::INVALID && span_id != SpanId::INVALID).then_some(Self {
+            trace_id,
+            span_id,
+            trace_flags,
+        })
+    }
+
+    /// Returns the ids of the span the event being formatted belongs to.
+    pub fn from_context<S, N>(ctx: &FmtContext<'_, S, N>) -> Option<Self>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> FormatFields<'a> + 'static,
+    {
+        ctx.parent_span().and_then(|span| Self::from_span(&span))
+    }
+
+    /// Returns the trace flags as a two digit hex string, e.g. `01` for sampled traces.
+    pub fn trace_flags_hex(&self) -> String {
+        format!("{:02x}", self.trace_flags.to_u8())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Layout {
+    Line,
+    Block,
+    Json(JsonLayout),
+}
+
+/// The rewrites applied to the JSON output.
+#[derive(Debug, Clone, Default)]
+struct JsonLayout {
+    schema: JsonSchema,
+    renames: Vec<(String, String)>,
+    numeric_timestamp: bool,
+}
+
+impl JsonLayout {
+    fn is_passthrough(&self) -> bool {
+        self.schema == JsonSchema::Generic && self.renames.is_empty()
+    }
+
+    // The numeric timestamp, which the wrapped formatter is configured not to write
+    fn timestamp(&self) -> Option<u128> {
+        self.numeric_timestamp.then(TimestampFormat::epoch_millis)
+    }
+
+    fn apply(&self, object: JsonObject, ids: Option<&OtelIds>) -> JsonObject {
+        let object = match self.timestamp() {
+            Some(millis) => {
+                let mut timed = JsonObject::default();
+                timed.insert_value("timestamp", millis);
+                timed.extend(object);
+                timed
+            }
+            None => object,
+        };
+        let object = self.schema.apply(object, ids);
+        if self.renames.is_empty() {
+            return object;
+        }
+        let mut renamed = JsonObject::default();
+        for (key, value) in object.0 {
+            let key = match self.renames.iter().find(|(from, _)| *from == key) {
+                Some((_, to)) => to.clone(),
+                None => key,
+            };
+            renamed.insert(key, value);
+        }
+        renamed
+    }
+}
+
+/// A JSON object which keeps its keys in the order they are inserted, with the values kept
+/// as they were written.
+#[derive(Debug, Default)]
+pub(crate) struct JsonObject(Vec<(String, Box<RawValue>)>);
+
+impl JsonObject {
+    /// Parse a JSON object, a repeated key keeps its first position and its last value.
+    pub(crate) fn parse(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.0.iter().position(|(k, _)| k == key)
+    }
+
+    #[cfg(test)]
+    fn get(&self, key: &str) -> Option<&RawValue> {
+        self.position(key).map(|index| &*self.0[index].1)
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<Box<RawValue>> {
+        self.position(key).map(|index| self.0.remove(index).1)
+    }
+
+    /// Insert a value, replacing the value of an existing key in place.
+    pub(crate) fn insert(&mut self, key: impl Into<String>, value: Box<RawValue>) {
+        let key = key.into();
+        match self.position(&key) {
+            Some(index) => self.0[index].1 = value,
+            None => self.0.push((key, value)),
+        }
+    }
+
+    pub(crate) fn insert_value(&mut self, key: impl Into<String>, value: impl Serialize) {
+        if let Ok(value) = to_raw_value(&value) {
+            self.insert(key, value);
+        }
+    }
+
+    pub(crate) fn extend(&mut self, other: JsonObject) {
+        for (key, value) in other.0 {
+            self.insert(key, value);
+        }
+    }
+
+    #[cfg(test)]
+    fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(key, _)| key.as_str())
+    }
+}
+
+impl Serialize for JsonObject {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ObjectVisitor;
+
+        impl<'de> Visitor<'de> for ObjectVisitor {
+            type Value = JsonObject;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut object = JsonObject::default();
+                while let Some((key, value)) = map.next_entry::<String, Box<RawValue>>()? {
+                    object.insert(key, value);
+                }
+                Ok(object)
+            }
+        }
+
+        deserializer.deserialize_map(ObjectVisitor)
+    }
+}
+
+/// A [`FormatEvent`] which adds the OpenTelemetry `trace_id`, `span_id` and `trace_flags`
+/// of the current span to every line written by the wrapped formatter.
+///
+/// - compact output gets `trace_id=… span_id=… trace_flags=…` at the end of the line,
+/// - pretty output gets an extra `otel trace_id: …` line,
+/// - JSON output gets three top-level keys, or is remapped to a vendor [`JsonSchema`].
+///
+/// The ids replace any key of the same name, such as a flattened event field. With the
+/// generic schema and no key renames, they are spliced into the line written by the wrapped
+/// formatter, which is only parsed when it already mentions one of them. A vendor schema or
+/// key renames parse every line into a JSON object and serialize it again, which roughly
+/// doubles the cost of formatting it.
+///
+/// Events outside of any span tracked by `tracing-opentelemetry` are written without ids.
+///
+/// `tracing-opentelemetry` makes the sampling decision of a root span when its first child
+/// span is created or when it closes, so lines written directly inside a root span before
+/// then carry the trace flags `00`.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_otel_extra::logs::OtelFormat;
+/// use tracing_subscriber::fmt;
+///
+/// let layer = fmt::layer::<tracing_subscriber::Registry>()
+///     .compact()
+///     .map_event_format(OtelFormat::compact);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OtelFormat<F> {
+    ansi: F,
+    plain: F,
+    layout: Layout,
+}
+
+impl<T: Clone> OtelFormat<Format<Compact, T>> {
+    /// Wrap a compact formatter.
+    pub fn compact(format: Format<Compact, T>) -> Self {
+        Self {
+            ansi: format.clone().with_ansi(true),
+            plain: format.with_ansi(false),
+            layout: Layout::Line,
+        }
+    }
+}
+
+impl<T: Clone> OtelFormat<Format<Pretty, T>> {
+    /// Wrap a pretty formatter.
+    pub fn pretty(format: Format<Pretty, T>) -> Self {
+        Self {
+            ansi: format.clone().with_ansi(true),
+            plain: format.with_ansi(false),
+            layout: Layout::Block,
+        }
+    }
+}
+
+impl<T: Clone> OtelFormat<Format<Json, T>> {
+    /// Wrap a JSON formatter.
+    pub fn json(format: Format<Json, T>) -> Self {
+        Self {
+            ansi: format.clone(),
+            plain: format,
+            layout: Layout::Json(JsonLayout::default()),
+        }
+    }
+
+    /// Remap the JSON output to the given vendor schema.
+    ///
+    /// The wrapped formatter should flatten the event fields and include the span list, as
+    /// `fmt::format().json().flatten_event(true)` does.
+    pub fn with_schema(mut self, schema: JsonSchema) -> Self {
+        if let Layout::Json(layout) = &mut self.layout {
+            layout.schema = schema;
+        }
+        self
+    }
+}
+
+impl OtelFormat<Format<Json, TimestampFormat>> {
+    /// Create a JSON formatter from [`JsonOptions`], including its key renames and
+    /// timestamp format.
+    ///
+    /// Epoch timestamps are written as the first key directly, as a number.
+    pub fn from_json_options(options: &JsonOptions) -> Self {
+        let numeric_timestamp = options.timestamp_format == TimestampFormat::EpochMillis;
+        let mut format = if numeric_timestamp {
+            Self::json(
+                options
+                    .format()
+                    .without_time()
+                    .with_timer(options.timestamp_format),
+            )
+        } else {
+            Self::json(options.format())
+        };
+        format.layout = Layout::Json(JsonLayout {
+            schema: JsonSchema::Generic,
+            renames: options.renames.clone(),
+            numeric_timestamp,
+        });
+        format
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for OtelFormat<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let inner = if writer.has_ansi_escapes() {
+            &self.ansi
+        } else {
+            &self.plain
+        };
+        // The wrapped formatter is configured with the ANSI setting of the destination,
+        // so buffering its output keeps the colors.
+        let buffered = || {
+            let mut buf = String::new();
+            inner.format_event(ctx, Writer::new(&mut buf), event)?;
+            Ok::<_, fmt::Error>(buf)
+        };
+
+        match (&self.layout, OtelIds::from_context(ctx)) {
+            (Layout::Line, Some(ids)) => {
+                let buf = buffered()?;
+                let line = buf.trim_end_matches('\n');
+                writeln!(
+                    writer,
+                    "{line} {TRACE_ID_FIELD}={} {SPAN_ID_FIELD}={} {TRACE_FLAGS_FIELD}={}",
+                    ids.trace_id,
+                    ids.span_id,
+                    ids.trace_flags_hex()
+                )
+            }
+            (Layout::Block, Some(ids)) => {
+                let buf = buffered()?;
+                let block = buf.trim_end_matches('\n');
+                writeln!(
+                    writer,
+                    "{block}\n    otel {TRACE_ID_FIELD}: {}, {SPAN_ID_FIELD}: {}, {TRACE_FLAGS_FIELD}: {}\n",
+                    ids.trace_id,
+                    ids.span_id,
+                    ids.trace_flags_hex()
+                )
+            }
+            (Layout::Json(layout), ids)
+                if layout.is_passthrough() && (ids.is_some() || layout.numeric_timestamp) =>
+            {
+                let line = buffered()?;
+                if ids.is_some() && mentions_ids(&line) {
+                    write_json_object(&mut writer, layout, &line, ids.as_ref())
+                } else {
+                    write_json_line(&mut writer, &line, layout.timestamp(), ids.as_ref())
+                }
+            }
+            (Layout::Json(layout), ids) if !layout.is_passthrough() => {
+                write_json_object(&mut writer, layout, &buffered()?, ids.as_ref())
+            }
+            _ => inner.format_event(ctx, writer, event),
+        }
+    }
+}
+
+// Whether a JSON line may already have one of the id keys, which the ids must replace
+fn mentions_ids(line: &str) -> bool {
+    [TRACE_ID_FIELD, SPAN_ID_FIELD, TRACE_FLAGS_FIELD]
+        .iter()
+        .any(|key| line.contains(&format!("\"{key}\":")))
+}
+
+// Write a JSON line of the wrapped formatter parsed and rearranged by the layout
+fn write_json_object(
+    writer: &mut Writer<'_>,
+    layout: &JsonLayout,
+    line: &str,
+    ids: Option<&OtelIds>,
+) -> fmt::Result {
+    // A line which is not a JSON object is written unchanged rather than dropped
+    let Ok(object) = JsonObject::parse(line) else {
+        return writer.write_str(line);
+    };
+    let object = layout.apply(object, ids);
+    let line = serde_json::to_string(&object).map_err(|_| fmt::Error)?;
+    writeln!(writer, "{line}")
+}
+
+// Write a JSON line of the wrapped formatter with the numeric timestamp as its first key and
+// the ids as its last keys, the line having none of them
+fn write_json_line(
+    writer: &mut Writer<'_>,
+    line: &str,
+    timestamp: Option<u128>,
+    ids: Option<&OtelIds>,
+) -> fmt::Result {
+    let Some(members) = line
+        .trim_end()
+        .strip_prefix('{')
+        .and_then(|line| line.strip_suffix('}'))
+    else {
+        return writer.write_str(line);
+    };
+    let members = members.trim();
+
+    writer.write_char('{')?;
+    if let Some(millis) = timestamp {
+        write!(writer, r#""timestamp":{millis}"#)?;
+        if !members.is_empty() {
+            writer.write_char(',')?;
+        }
+    }
+    writer.write_str(members)?;
+    if let Some(ids) = ids {
+        if timestamp.is_some() || !members.is_empty() {
+            writer.write_char(',')?;
+        }
+        write!(
+            writer,
+            r#""{TRACE_ID_FIELD}":"{}","{SPAN_ID_FIELD}":"{}","{TRACE_FLAGS_FIELD}":"{}""#,
+            ids.trace_id,
+            ids.span_id,
+            ids.trace_flags_hex()
+        )?;
+    }
+    writeln!(writer, "}}")
+}
+
+/// Insert the ids as top-level keys of a JSON log line.
+pub(crate) fn insert_ids(object: &mut JsonObject, ids: &OtelIds) {
+    object.insert_value(TRACE_ID_FIELD, ids.trace_id.to_string());
+    object.insert_value(SPAN_ID_FIELD, ids.span_id.to_string());
+    object.insert_value(TRACE_FLAGS_FIELD, ids.trace_flags_hex());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{logs::testing::Buffer, otel::opentelemetry::trace::TracerProvider as _};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing_subscriber::{layer::SubscriberExt, Layer, Registry};
+
+    fn capture<L>(layer: impl FnOnce(Buffer) -> L) -> (String, TraceId, SpanId)
+    where
+        L: Layer<Registry> + Send + Sync + 'static,
+    {
+        let buffer = Buffer::default();
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        let subscriber = tracing_subscriber::registry()
+            .with(layer(buffer.clone()))
+            .with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+        let ids = tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("outside");
+            // A root span is only sampled once its first child is created
+            let request = tracing::info_span!("request");
+            let _request = request.enter();
+            let span = tracing::info_span!("job", job.id = 7);
+            let _entered = span.enter();
+            tracing::info!(answer = 42, "inside");
+            tracing::dispatcher::get_default(|dispatch| {
+                let registry = dispatch.downcast_ref::<Registry>().unwrap();
+                let span = registry.span(&span.id().unwrap()).unwrap();
+                let ids = OtelIds::from_span(&span).unwrap();
+                (ids.trace_id, ids.span_id)
+            })
+        });
+        (buffer.contents(), ids.0, ids.1)
+    }
+
+    #[test]
+    fn test_compact_format() {
+        let (output, trace_id, span_id) = capture(|buffer| {
+            tracing_subscriber::fmt::layer()
+                .with_writer(buffer)
+                .with_ansi(false)
+                .compact()
+                .map_event_format(OtelFormat::compact)
+        });
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(!lines[0].contains(TRACE_ID_FIELD));
+        assert!(lines[1].ends_with(&format!(
+            "trace_id={trace_id} span_id={span_id} trace_flags=01"
+        )));
+    }
+
+    #[test]
+    fn test_pretty_format() {
+        let (output, trace_id, span_id) = capture(|buffer| {
+            tracing_subscriber::fmt::layer()
+                .with_writer(buffer)
+                .with_ansi(false)
+                .pretty()
+                .map_event_format(OtelFormat::pretty)
+        });
+        assert!(output.contains(&format!(
+            "    otel trace_id: {trace_id}, span_id: {span_id}, trace_flags: 01\n\n"
+        )));
+    }
+
+    #[test]
+    fn test_json_format() {
+        let (output, trace_id, span_id) = capture(|buffer| {
+            tracing_subscriber::fmt::layer()
+                .with_writer(buffer)
+                .json()
+                .flatten_event(true)
+                .map_event_format(OtelFormat::json)
+        });
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].get(TRACE_ID_FIELD).is_none());
+        assert_eq!(lines[1]["answer"], 42);
+        assert_eq!(lines[1][TRACE_ID_FIELD], trace_id.to_string());
+        assert_eq!(lines[1][SPAN_ID_FIELD], span_id.to_string());
+        assert_eq!(lines[1][TRACE_FLAGS_FIELD], "01");
+    }
+
+    #[test]
+    fn test_json_ids_replace_event_fields() {
+        let buffer = Buffer::default();
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        let subscriber = tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(buffer.clone())
+                    .json()
+                    .flatten_event(true)
+                    .map_event_format(OtelFormat::json),
+            )
+            .with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request");
+            let _request = request.enter();
+            let span = tracing::info_span!("job");
+            let _entered = span.enter();
+            tracing::info!(trace_id = "forwarded", "inside");
+        });
+
+        let output = buffer.contents();
+        assert_eq!(output.matches(r#""trace_id":"#).count(), 1, "{output}");
+        assert_eq!(output.matches(r#""span_id":"#).count(), 1, "{output}");
+        let line: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_ne!(line[TRACE_ID_FIELD], "forwarded");
+        assert_eq!(line[TRACE_FLAGS_FIELD], "01");
+    }
+
+    #[test]
+    fn test_json_options() {
+        let options = JsonOptions::default()
+            .with_span_list(false)
+            .with_target(false)
+            .with_rename("message", "msg")
+            .with_rename(TRACE_ID_FIELD, "traceId")
+            .with_timestamp_format(TimestampFormat::EpochMillis);
+        let (output, trace_id, _) = capture(|buffer| {
+            tracing_subscriber::fmt::layer()
+                .with_writer(buffer)
+                .fmt_fields(tracing_subscriber::fmt::format::JsonFields::new())
+                .event_format(OtelFormat::from_json_options(&options))
+        });
+        let line = JsonObject::parse(output.lines().last().unwrap()).unwrap();
+        let keys: Vec<&str> = line.keys().collect();
+        assert_eq!(
+            keys,
+            [
+                "timestamp",
+                "level",
+                "msg",
+                "answer",
+                "span",
+                "traceId",
+                "span_id",
+                "trace_flags"
+            ]
+        );
+        let line = serde_json::to_value(line).unwrap();
+        assert!(line["timestamp"].as_u64().unwrap() > 1_600_000_000_000);
+        assert_eq!(line["traceId"], trace_id.to_string());
+    }
+
+    #[test]
+    fn test_json_epoch_millis() {
+        let options = JsonOptions::default().with_timestamp_format(TimestampFormat::EpochMillis);
+        let (output, trace_id, _) = capture(|buffer| {
+            tracing_subscriber::fmt::layer()
+                .with_writer(buffer)
+                .fmt_fields(tracing_subscriber::fmt::format::JsonFields::new())
+                .event_format(OtelFormat::from_json_options(&options))
+        });
+        let lines: Vec<JsonObject> = output
+            .lines()
+            .map(|line| JsonObject::parse(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        for line in &lines {
+            assert_eq!(line.keys().next(), Some("timestamp"));
+            let millis: u64 = serde_json::from_str(line.get("timestamp").unwrap().get()).unwrap();
+            assert!(millis > 1_600_000_000_000);
+        }
+        assert!(lines[0].get(TRACE_ID_FIELD).is_none());
+        assert_eq!(
+            lines[1].get(TRACE_ID_FIELD).unwrap().get(),
+            format!("\"{trace_id}\"")
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use crate::otel::ProviderErrors;
+use crate::otel::{
+    opentelemetry::{metrics::MeterProvider, KeyValue},
+    OtelGuard,
+};
+use std::{ops::Deref, time::Duration};
+use tracing_appender::non_blocking::{ErrorCounter, WorkerGuard};
+
+/// The name of the counter of lines dropped by the non-blocking writers.
+pub const DROPPED_LINES_METRIC: &str = "log.dropped_lines";
+
+/// The worker guard of a non-blocking writer.
+///
+/// Dropping it flushes the lines buffered by the writer.
+#[derive(Debug)]
+pub struct AppenderGuard {
+    name: String,
+    error_counter: ErrorCounter,
+    guard: WorkerGuard,
+}
+
+impl AppenderGuard {
+    /// Create a guard for the named non-blocking writer.
+    pub fn new(name: impl Into<String>, guard: WorkerGuard, error_counter: ErrorCounter) -> Self {
+        Self {
+            name: name.into(),
+            error_counter,
+            guard,
+        }
+    }
+
+    /// The name of the writer, `stdout`, `stderr` or the filename prefix of a file appender.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of lines dropped because the buffer of the writer was full.
+    pub fn dropped_lines(&self) -> usize {
+        self.error_counter.dropped_lines()
+    }
+
+    /// Flush the buffered lines and stop the worker thread.
+    pub fn flush(self) {
+        drop(self.guard);
+    }
+}
+
+/// The guard returned by [`Logger::init`](crate::Logger::init).
+///
+/// It owns the OpenTelemetry providers and the worker guards of all non-blocking writers.
+/// On shutdown, or when dropped, the providers are shut down first, so that errors they
+/// log are still written, then the writers are flushed in the order they were created.
+///
+/// It dereferences to the [`OtelGuard`].
+#[derive(Debug)]
+pub struct LoggerGuard {
+    otel: Option<OtelGuard>,
+    appenders: Vec<AppenderGuard>,
+}
+
+impl LoggerGuard {
+    /// Create a guard owning the providers and the appender guards.
+    pub fn new(otel: OtelGuard, appenders: Vec<AppenderGuard>) -> Self {
+        Self {
+            otel: Some(otel),
+            appenders,
+        }
+    }
+
+    /// The worker guards of the non-blocking writers, in creation order.
+    pub fn appenders(&self) -> &[AppenderGuard] {
+        &self.appenders
+    }
+
+    /// The number of lines dropped by all non-blocking writers.
+    pub fn dropped_lines(&self) -> usize {
+        self.appenders
+            .iter()
+            .map(AppenderGuard::dropped_lines)
+            .sum()
+    }
+
+    /// Export the lines dropped by each non-blocking writer as the
+    /// [`log.dropped_lines`](DROPPED_LINES_METRIC) counter, with the writer name in the
+    /// `log.writer` attribute.
+    ///
+    /// [`Logger::init`](crate::Logger::init) registers it on the configured meter provider.
+    pub fn register_dropped_lines_counter(&self, meter_provider: &impl MeterProvider) {
+        if self.appenders.is_empty() {
+            return;
+        }
+        let counters: Vec<_> = self
+            .appenders
+            .iter()
+            .map(|appender| {
+                (
+                    [KeyValue::new("log.writer", appender.name.clone())],
+                    appender.error_counter.clone(),
+                )
+            })
+            .collect();
+        meter_provider
+            .meter(env!("CARGO_PKG_NAME"))
+            .u64_observable_counter(DROPPED_LINES_METRIC)
+            .with_description("Lines dropped because the buffer of a non-blocking writer was full")
+            .with_unit("{line}")
+            .with_callback(move |observer| {
+                for (attributes, counter) in &counters {
+                    observer.observe(counter.dropped_lines() as u64, attributes);
+                }
+            })
+            .build();
+    }
+
+    /// Shut down the providers, waiting at most `timeout` for them, then flush the
+    /// non-blocking writers.
+    ///
+    /// The writers are flushed even if a provider fails to shut down or misses the deadline,
+    /// see [`OtelGuard::shutdown_with_timeout`].
+    pub fn shutdown_with_timeout(mut self, timeout: Duration) -> Result<(), ProviderErrors> {
+        let result = self.shutdown_providers(timeout);
+        self.flush_appenders();
+        result
+    }
+
+    // Shut down the providers only, keeping the writers until the guard is dropped
+    pub(crate) fn shutdown_providers(&mut self, timeout: Duration) -> Result<(), ProviderErrors> {
+        match self.otel.take() {
+            Some(otel) => otel.shutdown_with_timeout(timeout),
+            None => Ok(()),
+        }
+    }
+
+    /// Shut down the providers without blocking the async runtime, then flush the
+    /// non-blocking writers, see [`OtelGuard::shutdown`]. Requires the `shutdown` feature.
+    #[cfg(feature = "shutdown")]
+    pub async fn shutdown(mut self) -> Result<(), ProviderErrors> {
+        let result = match self.otel.take() {
+            Some(otel) => otel.shutdown().await,
+            None => Ok(()),
+        };
+        self.flush_appenders();
+        result
+    }
+
+    fn flush_appenders(&mut self) {
+        for appender in self.appenders.drain(..) {
+            appender.flush();
+        }
+    }
+}
+
+impl From<OtelGuard> for LoggerGuard {
+    fn from(otel: OtelGuard) -> Self {
+        Self::new(otel, Vec::new())
+    }
+}
+
+impl Deref for LoggerGuard {
+    type Target = OtelGuard;
+
+    fn deref(&self) -> &Self::Target {
+        self.otel
+            .as_ref()
+            .expect("the OtelGuard is only taken on shutdown")
+    }
+}
+
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        drop(self.otel.take());
+        self.flush_appenders();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::testing::Buffer;
+    use std::io::Write;
+
+    #[test]
+    fn test_logger_guard_flushes_all_appenders() {
+        let buffers = [Buffer::default(), Buffer::default()];
+        let mut writers = Vec::new();
+        let appenders = buffers
+            .iter()
+            .enumerate()
+            .map(|(index, buffer)| {
+                let (writer, guard) = tracing_appender::non_blocking(buffer.clone());
+                let appender =
+                    AppenderGuard::new(format!("buffer-{index}"), guard, writer.error_counter());
+                writers.push(writer);
+                appender
+            })
+            .collect();
+        let guard = LoggerGuard::new(OtelGuard::new(None, None), appenders);
+        assert_eq!(guard.appenders()[1].name(), "buffer-1");
+
+        for writer in &mut writers {
+            writer.write_all(b"line\n").unwrap();
+        }
+        assert_eq!(guard.dropped_lines(), 0);
+        guard.shutdown_with_timeout(Duration::from_secs(5)).unwrap();
+
+        for buffer in buffers {
+            assert_eq!(buffer.lock().as_slice(), b"line\n");
+        }
+    }
+
+    #[test]
+    fn test_dropped_lines_counter() {
+        use opentelemetry_sdk::metrics::{
+            data::{AggregatedMetrics, MetricData},
+            InMemoryMetricExporter, SdkMeterProvider,
+        };
+        use tracing_appender::non_blocking::NonBlockingBuilder;
+
+        // Hold the buffer so that the worker blocks on the first line
+        let buffer = Buffer::default();
+        let lock = buffer.lock();
+        let (mut writer, guard) = NonBlockingBuilder::default()
+            .buffered_lines_limit(1)
+            .lossy(true)
+            .finish(buffer.clone());
+        let appender = AppenderGuard::new("app", guard, writer.error_counter());
+        let guard = LoggerGuard::new(OtelGuard::new(None, None), vec![appender]);
+        for _ in 0..4 {
+            writer.write_all(b"line\n").unwrap();
+        }
+        assert!(guard.dropped_lines() >= 2);
+
+        let exporter = InMemoryMetricExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(exporter.clone())
+            .build();
+        guard.register_dropped_lines_counter(&meter_provider);
+        meter_provider.force_flush().unwrap();
+
+        let metrics = exporter.get_finished_metrics().unwrap();
+        let metric = metrics
+            .iter()
+            .flat_map(|metrics| metrics.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .find(|metric| metric.name() == DROPPED_LINES_METRIC)
+            .unwrap();
+        let AggregatedMetrics::U64(MetricData::Sum(sum)) = metric.data() else {
+            panic!("unexpected data: {:?}", metric.data());
+        };
+        let point = sum.data_points().next().unwrap();
+        assert_eq!(point.value(), guard.dropped_lines() as u64);
+        assert_eq!(
+            point.attributes().collect::<Vec<_>>(),
+            [&KeyValue::new("log.writer", "app")]
+        );
+
+        drop(lock);
+        meter_provider.shutdown().unwrap();
+        guard.shutdown_with_timeout(Duration::from_secs(5)).unwrap();
+    }
+}
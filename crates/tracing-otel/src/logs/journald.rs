@@ -0,0 +1,365 @@
+use crate::logs::{
+    format::{OtelIds, SPAN_ID_FIELD, TRACE_FLAGS_FIELD, TRACE_ID_FIELD},
+    syslog::syslog_severity,
+};
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use std::{fmt, io};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// The socket of the native journal protocol.
+pub const DEFAULT_JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// The fields written by the layer itself, which event fields must not replace.
+const RESERVED_FIELDS: &[&str] = &[
+    "MESSAGE",
+    "PRIORITY",
+    "SYSLOG_IDENTIFIER",
+    "TARGET",
+    "CODE_FILE",
+    "CODE_LINE",
+    TRACE_ID_FIELD,
+    SPAN_ID_FIELD,
+    TRACE_FLAGS_FIELD,
+];
+
+/// Configuration of the journald output.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JournaldConfig {
+    /// Enable the journald output.
+    /// Defaults to true.
+    #[serde(default = "crate::logs::default::enable")]
+    pub enable: bool,
+
+    /// The journal socket. Defaults to `/run/systemd/journal/socket`.
+    #[serde(default)]
+    pub socket: Option<String>,
+
+    /// The `SYSLOG_IDENTIFIER` of the entries. Defaults to the service name.
+    #[serde(default)]
+    pub syslog_identifier: Option<String>,
+
+    /// A prefix added to the names of event fields, such as `F_`. It is made a valid journal
+    /// field name like the event field names.
+    #[serde(default)]
+    pub field_prefix: Option<String>,
+
+    /// A directive filter in `RUST_LOG` syntax, such as `warn`.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+impl Default for JournaldConfig {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            socket: None,
+            syslog_identifier: None,
+            field_prefix: None,
+            filter: None,
+        }
+    }
+}
+
+impl JournaldConfig {
+    /// Set the journal socket.
+    pub fn with_socket(mut self, socket: impl Into<String>) -> Self {
+        self.socket = Some(socket.into());
+        self
+    }
+
+    /// Set the `SYSLOG_IDENTIFIER`.
+    pub fn with_syslog_identifier(mut self, syslog_identifier: impl Into<String>) -> Self {
+        self.syslog_identifier = Some(syslog_identifier.into());
+        self
+    }
+
+    /// Set the directive filter.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Connect to the journal, using `service_name` as the default `SYSLOG_IDENTIFIER`.
+    pub fn build_layer(&self, service_name: &str) -> Result<JournaldLayer> {
+        let socket = self
+            .socket
+            .clone()
+            .unwrap_or_else(|| DEFAULT_JOURNALD_SOCKET.to_string());
+        let connection = connect(&socket)
+            .with_context(|| format!("Failed to connect to journald at {socket}"))?;
+        Ok(JournaldLayer {
+            socket: connection,
+            syslog_identifier: self
+                .syslog_identifier
+                .clone()
+                .unwrap_or_else(|| service_name.to_string()),
+            field_prefix: self
+                .field_prefix
+                .as_deref()
+                .filter(|prefix| !prefix.is_empty())
+                .map(field_name)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(unix)]
+type Socket = std::os::unix::net::UnixDatagram;
+
+#[cfg(not(unix))]
+type Socket = ();
+
+#[cfg(unix)]
+fn connect(path: &str) -> io::Result<Socket> {
+    let socket = std::os::unix::net::UnixDatagram::unbound()?;
+    socket.connect(path)?;
+    Ok(socket)
+}
+
+#[cfg(not(unix))]
+fn connect(_path: &str) -> io::Result<Socket> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "journald is not supported on this platform",
+    ))
+}
+
+/// A layer which sends events to systemd-journald using the native journal protocol.
+///
+/// Each event becomes an entry with `MESSAGE`, `PRIORITY`, `SYSLOG_IDENTIFIER`, `TARGET`,
+/// `CODE_FILE`, `CODE_LINE` and the OpenTelemetry `TRACE_ID`, `SPAN_ID` and `TRACE_FLAGS`.
+/// Event fields are added as structured fields, with their names uppercased and characters
+/// other than letters, digits and `_` replaced by `_`. An event field whose name would be one
+/// of the fields above gets an `F_` prefix, so `priority = "high"` becomes `F_PRIORITY`.
+///
+/// Entries are sent as single datagrams, so an entry larger than the socket buffer is dropped.
+pub struct JournaldLayer {
+    socket: Socket,
+    syslog_identifier: String,
+    field_prefix: String,
+}
+
+impl fmt::Debug for JournaldLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JournaldLayer")
+            .field("syslog_identifier", &self.syslog_identifier)
+            .field("field_prefix", &self.field_prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for JournaldLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        let mut payload = Vec::new();
+        put_field(
+            &mut payload,
+            "PRIORITY",
+            &syslog_severity(meta.level()).to_string(),
+        );
+        put_field(&mut payload, "SYSLOG_IDENTIFIER", &self.syslog_identifier);
+        put_field(&mut payload, "TARGET", meta.target());
+        if let Some(file) = meta.file() {
+            put_field(&mut payload, "CODE_FILE", file);
+        }
+        if let Some(line) = meta.line() {
+            put_field(&mut payload, "CODE_LINE", &line.to_string());
+        }
+
+        let ids = ctx
+            .event_span(event)
+            .and_then(|span| OtelIds::from_span(&span));
+        if let Some(ids) = ids {
+            put_field(&mut payload, TRACE_ID_FIELD, &ids.trace_id.to_string());
+            put_field(&mut payload, SPAN_ID_FIELD, &ids.span_id.to_string());
+            put_field(&mut payload, TRACE_FLAGS_FIELD, &ids.trace_flags_hex());
+        }
+
+        event.record(&mut JournaldVisitor {
+            payload: &mut payload,
+            field_prefix: &self.field_prefix,
+        });
+
+        // A logging sink has nowhere to report its own failures.
+        let _ = self.send(&payload);
+    }
+}
+
+impl JournaldLayer {
+    #[cfg(unix)]
+    fn send(&self, payload: &[u8]) -> io::Result<()> {
+        self.socket.send(payload).map(|_| ())
+    }
+
+    #[cfg(not(unix))]
+    fn send(&self, _payload: &[u8]) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct JournaldVisitor<'a> {
+    payload: &'a mut Vec<u8>,
+    field_prefix: &'a str,
+}
+
+impl Visit for JournaldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, &format!("{value:?}"));
+    }
+}
+
+impl JournaldVisitor<'_> {
+    fn record(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => put_field(self.payload, "MESSAGE", value),
+            // Skip the metadata fields added by `tracing-log`.
+            name if name.starts_with("log.") => {}
+            name => {
+                let name = format!("{}{}", self.field_prefix, field_name(name));
+                if RESERVED_FIELDS
+                    .iter()
+                    .any(|reserved| reserved.eq_ignore_ascii_case(&name))
+                {
+                    put_field(self.payload, &format!("F_{name}"), value);
+                } else {
+                    put_field(self.payload, &name, value);
+                }
+            }
+        }
+    }
+}
+
+/// Make a valid journal field name: uppercase letters, digits and `_`, not starting with `_`
+/// or a digit, which are reserved or invalid.
+fn field_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches('_');
+    if name.starts_with(|c: char| c.is_ascii_digit()) || name.is_empty() {
+        format!("F_{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Append a field in the native journal protocol format.
+///
+/// Values containing a newline are written as the name, a newline, the value length as a
+/// little-endian `u64` and the value, instead of `NAME=value`.
+fn put_field(payload: &mut Vec<u8>, name: &str, value: &str) {
+    let name = name.to_ascii_uppercase();
+    payload.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        payload.push(b'\n');
+        payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        payload.push(b'=');
+    }
+    payload.extend_from_slice(value.as_bytes());
+    payload.push(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("http.method"), "HTTP_METHOD");
+        assert_eq!(field_name("_private"), "PRIVATE");
+        assert_eq!(field_name("2xx"), "F_2XX");
+    }
+
+    #[test]
+    fn test_put_field() {
+        let mut payload = Vec::new();
+        put_field(&mut payload, "MESSAGE", "hello");
+        put_field(&mut payload, "MESSAGE", "two\nlines");
+        let mut expected = b"MESSAGE=hello\nMESSAGE\n".to_vec();
+        expected.extend_from_slice(&9u64.to_le_bytes());
+        expected.extend_from_slice(b"two\nlines\n");
+        assert_eq!(payload, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_journald_layer() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let path =
+            std::env::temp_dir().join(format!("tracing-otel-journald-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        let layer = JournaldConfig::default()
+            .with_socket(path.to_string_lossy())
+            .build_layer("my-service")
+            .unwrap();
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::warn!(user.id = 42, priority = "high", "login failed");
+        });
+
+        let mut buffer = [0; 4096];
+        let len = listener.recv(&mut buffer).unwrap();
+        let entry = String::from_utf8_lossy(&buffer[..len]);
+        assert!(entry.contains("PRIORITY=4\n"));
+        assert!(entry.contains("SYSLOG_IDENTIFIER=my-service\n"));
+        assert!(entry.contains("TARGET=tracing_otel_extra::logs::journald::tests\n"));
+        assert!(entry.contains("MESSAGE=login failed\n"));
+        assert!(entry.contains("USER_ID=42\n"));
+        assert!(entry.contains("F_PRIORITY=high\n"));
+        assert!(!entry.contains("\nPRIORITY=high\n"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_journald_field_prefix() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let path = std::env::temp_dir().join(format!(
+            "tracing-otel-journald-prefix-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        let layer = JournaldConfig {
+            field_prefix: Some("app-".to_string()),
+            ..JournaldConfig::default()
+        }
+        .with_socket(path.to_string_lossy())
+        .build_layer("my-service")
+        .unwrap();
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::info!(user.id = 42, "login");
+        });
+
+        let mut buffer = [0; 4096];
+        let len = listener.recv(&mut buffer).unwrap();
+        let entry = String::from_utf8_lossy(&buffer[..len]);
+        assert!(entry.contains("APP_USER_ID=42\n"));
+        assert!(entry.contains("MESSAGE=login\n"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
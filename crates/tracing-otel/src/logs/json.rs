@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tracing_subscriber::fmt::{
+    format::{Format, Json, Writer},
+    time::{FormatTime, SystemTime},
+};
+
+/// The format of the `timestamp` key in JSON logs.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// An RFC 3339 string, e.g. `2025-01-01T00:00:00.000000Z`.
+    #[serde(rename = "rfc3339")]
+    #[default]
+    Rfc3339,
+    /// The number of milliseconds since the Unix epoch.
+    #[serde(rename = "epoch_millis")]
+    EpochMillis,
+}
+
+impl TimestampFormat {
+    /// The number of milliseconds since the Unix epoch.
+    pub(crate) fn epoch_millis() -> u128 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    }
+}
+
+impl FormatTime for TimestampFormat {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        match self {
+            Self::Rfc3339 => SystemTime.format_time(w),
+            Self::EpochMillis => write!(w, "{}", Self::epoch_millis()),
+        }
+    }
+}
+
+/// Layout options for the JSON log formats.
+///
+/// They apply to [`LogFormat::Json`](crate::logs::LogFormat::Json) and to the vendor
+/// formats, which always flatten the event fields and ignore `flatten_event`.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_otel_extra::logs::{JsonOptions, TimestampFormat};
+///
+/// let options = JsonOptions::default()
+///     .with_span_list(false)
+///     .with_rename("message", "msg")
+///     .with_timestamp_format(TimestampFormat::EpochMillis);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct JsonOptions {
+    /// Include the current span in a `span` key. Defaults to `true`.
+    pub current_span: bool,
+
+    /// Include all spans in scope in a `spans` key. Defaults to `true`.
+    pub span_list: bool,
+
+    /// Include the event target. Defaults to `true`.
+    pub target: bool,
+
+    /// Include the source file name. Defaults to `false`.
+    pub file: bool,
+
+    /// Include the source line number. Defaults to `false`.
+    pub line_number: bool,
+
+    /// Include the thread name. Defaults to `false`.
+    pub thread_names: bool,
+
+    /// Include the thread id. Defaults to `false`.
+    pub thread_ids: bool,
+
+    /// Write the event fields at the top level instead of in a `fields` key.
+    /// Defaults to `true`.
+    pub flatten_event: bool,
+
+    /// Renames of top-level keys, applied last, e.g. `message=msg,fields=payload`.
+    #[serde(deserialize_with = "deserialize_renames")]
+    pub renames: Vec<(String, String)>,
+
+    /// The format of the `timestamp` key.
+    ///
+    /// * options: `rfc3339` | `epoch_millis`
+    pub timestamp_format: TimestampFormat,
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        Self {
+            current_span: true,
+            span_list: true,
+            target: true,
+            file: false,
+            line_number: false,
+            thread_names: false,
+            thread_ids: false,
+            flatten_event: true,
+            renames: Vec::new(),
+            timestamp_format: TimestampFormat::default(),
+        }
+    }
+}
+
+impl JsonOptions {
+    /// Set whether to include the current span.
+    pub fn with_current_span(mut self, current_span: bool) -> Self {
+        self.current_span = current_span;
+        self
+    }
+
+    /// Set whether to include the list of spans in scope.
+    pub fn with_span_list(mut self, span_list: bool) -> Self {
+        self.span_list = span_list;
+        self
+    }
+
+    /// Set whether to include the event target.
+    pub fn with_target(mut self, target: bool) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Set whether to include the source file name and line number.
+    pub fn with_file_and_line(mut self, enabled: bool) -> Self {
+        self.file = enabled;
+        self.line_number = enabled;
+        self
+    }
+
+    /// Set whether to include the thread name and id.
+    pub fn with_threads(mut self, enabled: bool) -> Self {
+        self.thread_names = enabled;
+        self.thread_ids = enabled;
+        self
+    }
+
+    /// Set whether to write the event fields at the top level.
+    pub fn with_flatten_event(mut self, flatten_event: bool) -> Self {
+        self.flatten_event = flatten_event;
+        self
+    }
+
+    /// Rename a top-level key.
+    ///
+    /// Renaming keys means parsing every line into a JSON object and serializing it again.
+    pub fn with_rename(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.renames.push((from.into(), to.into()));
+        self
+    }
+
+    /// Set the format of the `timestamp` key.
+    pub fn with_timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
+
+    /// Build the `tracing-subscriber` JSON formatter for these options.
+    ///
+    /// Key renames are applied afterwards by [`OtelFormat`](crate::logs::OtelFormat), which
+    /// also writes epoch timestamps as numbers, while this formatter writes them as strings.
+    pub fn format(&self) -> Format<Json, TimestampFormat> {
+        tracing_subscriber::fmt::format()
+            .json()
+            .flatten_event(self.flatten_event)
+            .with_current_span(self.current_span)
+            .with_span_list(self.span_list)
+            .with_target(self.target)
+            .with_file(self.file)
+            .with_line_number(self.line_number)
+            .with_thread_names(self.thread_names)
+            .with_thread_ids(self.thread_ids)
+            .with_timer(self.timestamp_format)
+    }
+}
+
+// Parse renames from `from=to,from2=to2` or from a map
+fn deserialize_renames<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct RenamesVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for RenamesVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a `from=to` list or a map of key renames")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            s.split(',')
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| {
+                    let (from, to) = s
+                        .split_once('=')
+                        .ok_or_else(|| E::custom(format!("Invalid rename: '{s}'")))?;
+                    let (from, to) = (from.trim(), to.trim());
+                    if from.is_empty() || to.is_empty() {
+                        return Err(E::custom(format!("Empty key in rename: '{s}'")));
+                    }
+                    Ok((from.to_string(), to.to_string()))
+                })
+                .collect()
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut renames = Vec::new();
+            while let Some(rename) = map.next_entry()? {
+                renames.push(rename);
+            }
+            Ok(renames)
+        }
+    }
+
+    deserializer.deserialize_any(RenamesVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_json_options() {
+        let options: JsonOptions = serde_json::from_str(
+            r#"{"span_list": false, "renames": "message=msg, fields=payload", "timestamp_format": "epoch_millis"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            options,
+            JsonOptions::default()
+                .with_span_list(false)
+                .with_rename("message", "msg")
+                .with_rename("fields", "payload")
+                .with_timestamp_format(TimestampFormat::EpochMillis)
+        );
+
+        let options: JsonOptions =
+            serde_json::from_str(r#"{"renames": {"level": "severity"}}"#).unwrap();
+        assert_eq!(options.renames, vec![("level".into(), "severity".into())]);
+
+        assert!(serde_json::from_str::<JsonOptions>(r#"{"renames": "message"}"#).is_err());
+    }
+
+    #[test]
+    fn test_epoch_millis_timestamp() {
+        let mut timestamp = String::new();
+        TimestampFormat::EpochMillis
+            .format_time(&mut Writer::new(&mut timestamp))
+            .unwrap();
+        assert!(timestamp.parse::<u64>().unwrap() > 1_600_000_000_000);
+    }
+}
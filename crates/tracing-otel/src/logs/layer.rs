@@ -1,3 +1,7 @@
+use crate::{
+    logs::{attributes::parse_attributes, SpanFieldPrefix},
+    otel::Detector,
+};
 use anyhow::Result;
 use opentelemetry::KeyValue;
 use serde::{Deserialize, Serialize};
@@ -13,6 +17,18 @@ pub enum LogFormat {
     Pretty,
     #[serde(rename = "json")]
     Json,
+    #[serde(rename = "logfmt")]
+    Logfmt,
+    /// JSON in the Elastic Common Schema.
+    #[serde(rename = "ecs")]
+    Ecs,
+    /// JSON in the Google Cloud Logging structured format. The trace is linked to the project
+    /// set in the `GOOGLE_CLOUD_PROJECT` environment variable.
+    #[serde(rename = "gcp")]
+    Gcp,
+    /// JSON with the Datadog trace correlation fields.
+    #[serde(rename = "datadog")]
+    Datadog,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
@@ -28,6 +44,21 @@ pub enum LogRollingRotation {
     Never,
 }
 
+/// Where console output is written.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ConsoleTarget {
+    /// Write everything to stdout.
+    #[serde(rename = "stdout")]
+    #[default]
+    Stdout,
+    /// Write everything to stderr.
+    #[serde(rename = "stderr")]
+    Stderr,
+    /// Write WARN and ERROR to stderr, and everything else to stdout.
+    #[serde(rename = "split")]
+    Split,
+}
+
 // Parse log format from string
 pub fn deserialize_log_format<'de, D>(deserializer: D) -> Result<LogFormat, D::Error>
 where
@@ -38,41 +69,52 @@ where
         "compact" => Ok(LogFormat::Compact),
         "pretty" => Ok(LogFormat::Pretty),
         "json" => Ok(LogFormat::Json),
+        "logfmt" => Ok(LogFormat::Logfmt),
+        "ecs" => Ok(LogFormat::Ecs),
+        "gcp" => Ok(LogFormat::Gcp),
+        "datadog" => Ok(LogFormat::Datadog),
         _ => Err(serde::de::Error::custom(format!(
             "Invalid log format: '{s}'"
         ))),
     }
 }
 
-// Parse attributes from string
+/// Parse the prefix of logfmt span field keys: `none`, `span_name`, or any other string
+/// used as a fixed prefix, such as `span.`.
+pub fn deserialize_span_field_prefix<'de, D>(deserializer: D) -> Result<SpanFieldPrefix, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(match s.trim() {
+        "" => SpanFieldPrefix::None,
+        prefix if prefix.eq_ignore_ascii_case("none") => SpanFieldPrefix::None,
+        prefix if prefix.eq_ignore_ascii_case("span_name") => SpanFieldPrefix::SpanName,
+        prefix => SpanFieldPrefix::Fixed(prefix.to_string()),
+    })
+}
+
+/// Deserialize typed attributes, see [`attributes`](crate::logs::attributes).
 pub fn deserialize_attributes<'de, D>(deserializer: D) -> Result<Vec<KeyValue>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     let s = String::deserialize(deserializer)?;
-    if s.is_empty() {
-        return Ok(Vec::new());
-    }
+    parse_attributes(&s).map_err(serde::de::Error::custom)
+}
 
+/// Deserialize a comma separated list of resource detectors, or `all`.
+pub fn deserialize_resource_detectors<'de, D>(deserializer: D) -> Result<Vec<Detector>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    if s.trim().eq_ignore_ascii_case("all") {
+        return Ok(Detector::ALL.to_vec());
+    }
     s.split(',')
         .filter(|s| !s.trim().is_empty())
-        .map(|s| {
-            let s = s.trim();
-            let (key, value) = s
-                .split_once('=')
-                .ok_or_else(|| serde::de::Error::custom(format!("Invalid attribute: '{s}'")))?;
-
-            let key = key.trim();
-            let value = value.trim();
-
-            if key.is_empty() || value.is_empty() {
-                return Err(serde::de::Error::custom(format!(
-                    "Empty key or value: '{s}'"
-                )));
-            }
-
-            Ok(KeyValue::new(key.to_string(), value.to_string()))
-        })
+        .map(|s| s.parse().map_err(serde::de::Error::custom))
         .collect()
 }
 
@@ -90,6 +132,18 @@ mod tests {
     use serde::de::IntoDeserializer;
     type StrDeserializer<'a> = serde::de::value::StrDeserializer<'a, serde::de::value::Error>;
 
+    #[test]
+    fn test_parse_span_field_prefix() {
+        let parse =
+            |s: &str| deserialize_span_field_prefix::<StrDeserializer>(s.into_deserializer());
+        assert_eq!(parse("none").unwrap(), SpanFieldPrefix::None);
+        assert_eq!(parse("SPAN_NAME").unwrap(), SpanFieldPrefix::SpanName);
+        assert_eq!(
+            parse("span.").unwrap(),
+            SpanFieldPrefix::Fixed("span.".to_string())
+        );
+    }
+
     #[test]
     fn test_parse_log_format() {
         assert_eq!(
@@ -104,6 +158,22 @@ mod tests {
             deserialize_log_format::<StrDeserializer>("json".into_deserializer()).unwrap(),
             LogFormat::Json
         );
+        assert_eq!(
+            deserialize_log_format::<StrDeserializer>("logfmt".into_deserializer()).unwrap(),
+            LogFormat::Logfmt
+        );
+        assert_eq!(
+            deserialize_log_format::<StrDeserializer>("ecs".into_deserializer()).unwrap(),
+            LogFormat::Ecs
+        );
+        assert_eq!(
+            deserialize_log_format::<StrDeserializer>("GCP".into_deserializer()).unwrap(),
+            LogFormat::Gcp
+        );
+        assert_eq!(
+            deserialize_log_format::<StrDeserializer>("datadog".into_deserializer()).unwrap(),
+            LogFormat::Datadog
+        );
 
         assert_eq!(
             deserialize_log_format::<StrDeserializer>("default_string".into_deserializer())
@@ -156,4 +226,20 @@ mod tests {
         )
         .is_err());
     }
+
+    #[test]
+    fn test_parse_resource_detectors() {
+        let parse =
+            |s: &str| deserialize_resource_detectors::<StrDeserializer>(s.into_deserializer());
+        assert_eq!(parse("").unwrap(), vec![]);
+        assert_eq!(
+            parse("host, Process,kubernetes").unwrap(),
+            vec![Detector::Host, Detector::Process, Detector::K8s]
+        );
+        assert_eq!(parse("all").unwrap(), Detector::ALL.to_vec());
+        assert_eq!(
+            parse("host,docker").unwrap_err().to_string(),
+            "Invalid resource detector: 'docker', expected one of host, os, process, container, k8s"
+        );
+    }
 }
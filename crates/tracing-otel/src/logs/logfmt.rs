@@ -0,0 +1,410 @@
+use crate::logs::format::{OtelIds, SPAN_ID_FIELD, TRACE_FLAGS_FIELD, TRACE_ID_FIELD};
+use std::fmt;
+use tracing::{
+    field::{Field, Visit},
+    span::Record,
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    field::RecordFields,
+    fmt::{
+        format::Writer,
+        time::{FormatTime, SystemTime},
+        FmtContext, FormatEvent, FormatFields, FormattedFields,
+    },
+    registry::LookupSpan,
+};
+
+/// How the keys of span fields are prefixed in logfmt output.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum SpanFieldPrefix {
+    /// Use the field name as is, e.g. `http.method`.
+    #[default]
+    None,
+    /// Prefix the field name with the span name, e.g. `request.http.method`.
+    SpanName,
+    /// Prefix the field name with a fixed string, e.g. `span.http.method` for `"span."`.
+    Fixed(String),
+}
+
+/// A [`FormatEvent`] which writes events in [logfmt](https://brandur.org/logfmt).
+///
+/// Each line contains `ts`, `level`, `target` and `msg`, followed by the event fields, the
+/// fields of every span in scope from the root to the leaf, and the OpenTelemetry
+/// `trace_id`, `span_id` and `trace_flags`. Values containing spaces, quotes, `=` or control
+/// characters are quoted and escaped, so that every event stays on a single line. When the
+/// OpenTelemetry ids are written, an event or span field with the same key is skipped.
+///
+/// Span fields are read from the pairs stored by [`LogfmtFields`] when they are recorded, so
+/// the layer must use `.fmt_fields(LogfmtFields::new())`, set before the event format.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_otel_extra::logs::{LogfmtFields, LogfmtFormat, SpanFieldPrefix};
+/// use tracing_subscriber::fmt;
+///
+/// let layer = fmt::layer::<tracing_subscriber::Registry>()
+///     .fmt_fields(LogfmtFields::new())
+///     .event_format(LogfmtFormat::new().with_span_field_prefix(SpanFieldPrefix::SpanName));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogfmtFormat<T = SystemTime> {
+    timer: T,
+    span_field_prefix: SpanFieldPrefix,
+}
+
+impl Default for LogfmtFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogfmtFormat {
+    /// Create a new `LogfmtFormat` with RFC 3339 timestamps and unprefixed span fields.
+    pub fn new() -> Self {
+        Self {
+            timer: SystemTime,
+            span_field_prefix: SpanFieldPrefix::default(),
+        }
+    }
+}
+
+impl<T> LogfmtFormat<T> {
+    /// Set how the keys of span fields are prefixed.
+    pub fn with_span_field_prefix(mut self, span_field_prefix: SpanFieldPrefix) -> Self {
+        self.span_field_prefix = span_field_prefix;
+        self
+    }
+
+    /// Set the timer used for the `ts` key.
+    pub fn with_timer<T2>(self, timer: T2) -> LogfmtFormat<T2> {
+        LogfmtFormat {
+            timer,
+            span_field_prefix: self.span_field_prefix,
+        }
+    }
+
+    fn span_field_key(&self, span_name: &str, field: &str) -> String {
+        match &self.span_field_prefix {
+            SpanFieldPrefix::None => field.to_string(),
+            SpanFieldPrefix::SpanName => format!("{}.{field}", escape_key(span_name)),
+            SpanFieldPrefix::Fixed(prefix) => format!("{}{field}", escape_key(prefix)),
+        }
+    }
+}
+
+impl<S, T> FormatEvent<S, LogfmtFields> for LogfmtFormat<T>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    T: FormatTime,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, LogfmtFields>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+
+        let mut timestamp = String::new();
+        if self
+            .timer
+            .format_time(&mut Writer::new(&mut timestamp))
+            .is_ok()
+        {
+            write_pair(&mut writer, "ts", &timestamp)?;
+            writer.write_char(' ')?;
+        }
+        write_pair(&mut writer, "level", &meta.level().as_str().to_lowercase())?;
+        writer.write_char(' ')?;
+        write_pair(&mut writer, "target", meta.target())?;
+
+        // The ids are written last, replacing the fields with the same keys
+        let ids = OtelIds::from_context(ctx);
+        let is_id = |key: &str| {
+            ids.is_some() && [TRACE_ID_FIELD, SPAN_ID_FIELD, TRACE_FLAGS_FIELD].contains(&key)
+        };
+
+        let mut visitor = LogfmtVisitor::default();
+        event.record(&mut visitor);
+        if let Some(message) = &visitor.message {
+            writer.write_char(' ')?;
+            write_pair(&mut writer, "msg", message)?;
+        }
+        for (key, value) in &visitor.fields {
+            if !is_id(key) {
+                writer.write_char(' ')?;
+                write_pair(&mut writer, key, value)?;
+            }
+        }
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                let Some(fields) = extensions.get::<FormattedFields<LogfmtFields>>() else {
+                    continue;
+                };
+                for (field, value) in stored_pairs(fields) {
+                    let key = self.span_field_key(span.name(), field);
+                    if !is_id(&key) {
+                        write!(writer, " {key}={value}")?;
+                    }
+                }
+            }
+        }
+
+        if let Some(ids) = ids {
+            writer.write_char(' ')?;
+            write_pair(&mut writer, TRACE_ID_FIELD, &ids.trace_id.to_string())?;
+            writer.write_char(' ')?;
+            write_pair(&mut writer, SPAN_ID_FIELD, &ids.span_id.to_string())?;
+            writer.write_char(' ')?;
+            write_pair(&mut writer, TRACE_FLAGS_FIELD, &ids.trace_flags_hex())?;
+        }
+
+        writeln!(writer)
+    }
+}
+
+/// The [`FormatFields`] used with [`LogfmtFormat`].
+///
+/// It stores the fields of a span as escaped logfmt pairs when they are recorded, a later
+/// value replacing the earlier one, so events only copy them.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogfmtFields;
+
+impl LogfmtFields {
+    /// Create a new `LogfmtFields`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<'writer> FormatFields<'writer> for LogfmtFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        let mut visitor = LogfmtVisitor::default();
+        fields.record(&mut visitor);
+        for (key, value) in visitor.pairs() {
+            write_pair(&mut writer, key, &value)?;
+            writer.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    fn add_fields(
+        &self,
+        current: &'writer mut FormattedFields<Self>,
+        fields: &Record<'_>,
+    ) -> fmt::Result {
+        let mut visitor = LogfmtVisitor::default();
+        fields.record(&mut visitor);
+        let pairs: Vec<_> = visitor.pairs().collect();
+        let mut stored = String::with_capacity(current.fields.len());
+        for (key, value) in stored_pairs(current) {
+            if !pairs.iter().any(|(name, _)| escape_key(name) == key) {
+                stored.push_str(key);
+                stored.push('=');
+                stored.push_str(value);
+                stored.push('\n');
+            }
+        }
+        let mut writer = Writer::new(&mut stored);
+        for (key, value) in pairs {
+            write_pair(&mut writer, key, &value)?;
+            writer.write_char('\n')?;
+        }
+        current.fields = stored;
+        Ok(())
+    }
+}
+
+// The escaped key and value of each pair stored by `LogfmtFields`, one per line
+fn stored_pairs(fields: &FormattedFields<LogfmtFields>) -> impl Iterator<Item = (&str, &str)> {
+    fields
+        .fields
+        .lines()
+        .filter_map(|line| line.split_once('='))
+}
+
+#[derive(Default)]
+struct LogfmtVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for LogfmtVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{value:?}"));
+    }
+}
+
+impl LogfmtVisitor {
+    // The fields of a span, where `message` is an ordinary field
+    fn pairs(self) -> impl Iterator<Item = (&'static str, String)> {
+        self.message
+            .map(|message| ("message", message))
+            .into_iter()
+            .chain(self.fields)
+    }
+
+    fn record(&mut self, field: &Field, value: String) {
+        match field.name() {
+            "message" => self.message = Some(value),
+            // Skip the metadata fields added by `tracing-log`.
+            name if name.starts_with("log.") => {}
+            name => self.fields.push((name, value)),
+        }
+    }
+}
+
+// Replace the characters which cannot appear in a key
+fn escape_key(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c == '=' || c == '"' || c.is_whitespace() || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Write a `key=value` pair, quoting and escaping the value if needed.
+fn write_pair(writer: &mut Writer<'_>, key: &str, value: &str) -> fmt::Result {
+    writer.write_str(&escape_key(key))?;
+    writer.write_char('=')?;
+
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == '=' || c == '"' || c == '\\' || c.is_whitespace() || c.is_control());
+    if !needs_quotes {
+        return writer.write_str(value);
+    }
+
+    writer.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => writer.write_str("\\\"")?,
+            '\\' => writer.write_str("\\\\")?,
+            '\n' => writer.write_str("\\n")?,
+            '\r' => writer.write_str("\\r")?,
+            '\t' => writer.write_str("\\t")?,
+            c if c.is_control() => write!(writer, "\\u{{{:04x}}}", c as u32)?,
+            c => writer.write_char(c)?,
+        }
+    }
+    writer.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{logs::testing::Buffer, otel::opentelemetry::trace::TracerProvider as _};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn write_value(value: &str) -> String {
+        let mut output = String::new();
+        write_pair(&mut Writer::new(&mut output), "key", value).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_write_pair_escaping() {
+        assert_eq!(write_value("plain"), "key=plain");
+        assert_eq!(write_value(""), r#"key="""#);
+        assert_eq!(write_value("two words"), r#"key="two words""#);
+        assert_eq!(write_value(r#"say "hi""#), r#"key="say \"hi\"""#);
+        assert_eq!(write_value("a=b"), r#"key="a=b""#);
+        assert_eq!(write_value("line\nbreak"), r#"key="line\nbreak""#);
+        assert_eq!(write_value(r"C:\dir"), r#"key="C:\\dir""#);
+    }
+
+    #[test]
+    fn test_logfmt_format() {
+        let buffer = Buffer::default();
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        let subscriber = tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(buffer.clone())
+                    .fmt_fields(LogfmtFields::new())
+                    .event_format(
+                        LogfmtFormat::new().with_span_field_prefix(SpanFieldPrefix::SpanName),
+                    ),
+            )
+            .with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+        tracing::subscriber::with_default(subscriber, || {
+            // A root span is only sampled once its first child is created
+            let request = tracing::info_span!("request");
+            let _request = request.enter();
+            let span = tracing::info_span!("job", job.name = "nightly report", attempt = 2);
+            let _entered = span.enter();
+            tracing::warn!(reason = "disk \"full\"\nretrying", "job failed");
+        });
+
+        let output = buffer.contents();
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.starts_with("ts="));
+        assert!(output.contains(
+            r#" level=warn target=tracing_otel_extra::logs::logfmt::tests msg="job failed" reason="disk \"full\"\nretrying" job.job.name="nightly report" job.attempt=2 trace_id="#
+        ));
+        assert!(output.trim_end().ends_with("trace_flags=01"));
+    }
+
+    #[test]
+    fn test_span_fields_recorded_later() {
+        let buffer = Buffer::default();
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        let subscriber = tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(buffer.clone())
+                    .fmt_fields(LogfmtFields::new())
+                    .event_format(LogfmtFormat::new()),
+            )
+            .with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request");
+            let _request = request.enter();
+            // like the axum request span, which records the trace id of its parent context
+            let span = tracing::info_span!(
+                "job",
+                trace_id = tracing::field::Empty,
+                status = "pending",
+                trace_flags = "00"
+            );
+            span.record("trace_id", "4bf92f3577b34da6a3ce929d0e0e4736");
+            span.record("status", "done");
+            let _entered = span.enter();
+            tracing::info!(span_id = "event", "finished");
+        });
+
+        let output = buffer.contents();
+        assert_eq!(output.matches("trace_id=").count(), 1, "{output}");
+        assert_eq!(output.matches("span_id=").count(), 1, "{output}");
+        assert_eq!(output.matches("trace_flags=").count(), 1, "{output}");
+        assert_eq!(output.matches("status=").count(), 1, "{output}");
+        assert!(
+            output.contains(" msg=finished status=done trace_id="),
+            "{output}"
+        );
+        assert!(
+            !output.contains("4bf92f3577b34da6a3ce929d0e0e4736"),
+            "{output}"
+        );
+    }
+}
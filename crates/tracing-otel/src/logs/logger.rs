@@ -8,11 +8,12 @@
 //!
 //! - Builder-style configuration API
 //! - Environment variable support (with "env" feature)
-//! - Multiple log formats (compact, pretty, json)
+//! - Multiple log formats (compact, pretty, json, logfmt, ecs, gcp, datadog)
 //! - Configurable sampling and metrics collection
 //! - Custom resource attributes
 //! - Optional console output
 //! - Optional file output
+//! - Optional syslog (RFC 5424) and journald output
 //!
 //! # Quick Start
 //!
@@ -46,10 +47,9 @@
 //!         .with_sample_ratio(0.5)
 //!         .with_metrics_interval_secs(60)
 //!         .with_console_enabled(true)  // Enable console output
-//!         .with_attributes(vec![
-//!             KeyValue::new("environment", "production"),
-//!             KeyValue::new("version", "1.0.0"),
-//!         ])
+//!         .with_service_version("1.0.0")
+//!         .with_deployment_environment("production")
+//!         .with_attributes(vec![KeyValue::new("team", "payments")])
 //!         .init()?;
 //!
 //!     // Your application code here...
@@ -78,13 +78,17 @@
 //!
 //! ## File Only
 //! ```rust,no_run
-//! use tracing_otel_extra::{Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
+//! use tracing_otel_extra::{LogCompression, Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
 //! let file_appender = LoggerFileAppender {
 //!     enable: true,
+//!     name: None,
+//!     filter: None,
 //!     non_blocking: false,
+//!     buffered_lines_limit: None,
+//!     lossy: true,
 //!     level: None,
 //!     ansi: false,
 //!     format: Some(LogFormat::Json),
@@ -93,6 +97,11 @@
 //!     filename_prefix: Some("myapp".to_string()),
 //!     filename_suffix: Some("log".to_string()),
 //!     max_log_files: 10,
+//!     max_bytes: Some(100 * 1024 * 1024),
+//!     max_total_bytes: Some(1024 * 1024 * 1024),
+//!     max_age_secs: Some(7 * 24 * 60 * 60),
+//!     compression: LogCompression::Gzip,
+//!     json: None,
 //! };
 //!
 //! let guard = Logger::new("my-service")
@@ -106,13 +115,17 @@
 //!
 //! ## Both Console and File
 //! ```rust,no_run
-//! use tracing_otel_extra::{Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
+//! use tracing_otel_extra::{LogCompression, Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
 //!     let file_appender = LoggerFileAppender {
 //!         enable: true,
+//!         name: None,
+//!         filter: None,
 //!         non_blocking: false,
+//!         buffered_lines_limit: None,
+//!         lossy: true,
 //!         level: None,
 //!         ansi: false,
 //!         format: Some(LogFormat::Json),
@@ -121,6 +134,11 @@
 //!         filename_prefix: Some("myapp".to_string()),
 //!         filename_suffix: Some("log".to_string()),
 //!         max_log_files: 10,
+//!         max_bytes: None,
+//!         max_total_bytes: None,
+//!         max_age_secs: None,
+//!         compression: LogCompression::None,
+//!         json: None,
 //!     };
 //!
 //!     let guard = Logger::new("my-service")
@@ -132,6 +150,30 @@
 //! }
 //! ```
 //!
+//! ## Multiple File Appenders
+//! ```rust,no_run
+//! use tracing_otel_extra::{LogFormat, Logger, LoggerFileAppender};
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let guard = Logger::new("my-service")
+//!         .with_file_appenders(vec![
+//!             // app.log with INFO and above
+//!             LoggerFileAppender::new("app").with_filter("info"),
+//!             // error.log with WARN and above
+//!             LoggerFileAppender::new("error").with_filter("warn"),
+//!             // audit.log with only the events of the `audit` target
+//!             LoggerFileAppender::new("audit")
+//!                 .with_filter("audit=info")
+//!                 .with_format(LogFormat::Json)
+//!                 .with_dir("/var/log/audit"),
+//!         ])
+//!         .init()?;
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
 //! # Environment Variables
 //!
 //! When using the "env" feature, you can configure the logger through environment variables:
@@ -158,28 +200,111 @@
 //! | Variable | Description | Default |
 //! |----------|-------------|---------|
 //! | `LOG_SERVICE_NAME` | Service name | Crate name |
-//! | `LOG_FORMAT` | Log format (`compact`, `pretty`, `json`) | `compact` |
+//! | `LOG_SERVICE_VERSION` | `service.version` resource attribute | - |
+//! | `LOG_SERVICE_NAMESPACE` | `service.namespace` resource attribute | - |
+//! | `LOG_SERVICE_INSTANCE_ID` | `service.instance.id` resource attribute | Random UUID |
+//! | `LOG_DEPLOYMENT_ENVIRONMENT` | `deployment.environment.name` and `deployment.environment` resource attributes | - |
+//! | `LOG_FORMAT` | Log format (`compact`, `pretty`, `json`, `logfmt`, `ecs`, `gcp`, `datadog`) | `compact` |
+//! | `LOG_LOGFMT_SPAN_FIELD_PREFIX` | Prefix of span field keys in logfmt output (`none`, `span_name`, or a fixed prefix such as `span.`) | `none` |
 //! | `LOG_SPAN_EVENTS` | Span events (`FMT::NEW`, `FMT::ENTER`, `FMT::EXIT`, `FMT::CLOSE`, `FMT::NONE`, `FMT::ACTIVE`, `FMT::FULL`) | `FMT::NEW | FMT::CLOSE` |
 //! | `LOG_ANSI` | Enable ANSI colors | `true` |
 //! | `LOG_LEVEL` | Log level | `info` |
 //! | `LOG_SAMPLE_RATIO` | Sampling ratio (0.0-1.0) | `1.0` |
+//! | `LOG_PARENT_BASED_SAMPLING` | Follow the sampling decision of the parent span | `true` |
 //! | `LOG_METRICS_INTERVAL_SECS` | Metrics collection interval | `30` |
-//! | `LOG_ATTRIBUTES` | Additional attributes (`key=value,key2=value2`) | - |
+//! | `LOG_SDK_DISABLED` | Disable OpenTelemetry export, keeping the log outputs | `false` |
+//! | `LOG_TRACES_ENABLED` | Export traces over OTLP | `true` |
+//! | `LOG_METRICS_ENABLED` | Export metrics over OTLP | `true` |
+//! | `LOG_ATTRIBUTES` | Additional typed attributes (`key=value,replicas=3,zones=[a,b]`), see [`attributes`](crate::logs::attributes) | - |
+//! | `LOG_RESOURCE_DETECTORS` | Resource detectors (`host`, `os`, `process`, `container`, `k8s`, or `all`) | - |
 //! | `LOG_CONSOLE_ENABLED` | Enable console output | `true` |
+//! | `LOG_CONSOLE_TARGET` | Console stream (`stdout`, `stderr`, `split` for WARN and above on stderr) | `stdout` |
+//! | `LOG_CONSOLE_NON_BLOCKING` | Write console output from a background thread | `false` |
+//! | `LOG_CONSOLE_BUFFERED_LINES_LIMIT` | Lines buffered by the non-blocking console writer | `128000` |
+//! | `LOG_CONSOLE_LOSSY` | Drop lines when the console buffer is full, instead of blocking | `true` |
 //!
 //! ### File Logging Environment Variables
 //!
 //! | Variable | Description | Default |
 //! |----------|-------------|---------|
-//! | `LOG_FILE_ENABLE` | Enable file logging | `false` |
+//! | `LOG_FILE_ENABLE` | Enable the file appender configured by the `LOG_FILE_*` variables | `true` |
 //! | `LOG_FILE_NON_BLOCKING` | Enable non-blocking file logging | `false` |
+//! | `LOG_FILE_BUFFERED_LINES_LIMIT` | Lines buffered by the non-blocking file writer | `128000` |
+//! | `LOG_FILE_LOSSY` | Drop lines when the file buffer is full, instead of blocking | `true` |
 //! | `LOG_FILE_LEVEL` | File log level | `info` |
-//! | `LOG_FILE_FORMAT` | File log format (`compact`, `pretty`, `json`) | `compact` |
+//! | `LOG_FILE_FORMAT` | File log format (`compact`, `pretty`, `json`, `logfmt`, `ecs`, `gcp`, `datadog`) | `compact` |
 //! | `LOG_FILE_ROTATION` | File rotation (`minutely`, `hourly`, `daily`, `never`) | `hourly` |
 //! | `LOG_FILE_DIR` | Log directory | `./logs` |
 //! | `LOG_FILE_FILENAME_PREFIX` | Log filename prefix | `app` |
 //! | `LOG_FILE_FILENAME_SUFFIX` | Log filename suffix | `log` |
 //! | `LOG_FILE_MAX_LOG_FILES` | Maximum number of log files to keep | `5` |
+//! | `LOG_FILE_MAX_BYTES` | Rotate the current file at this size (`104857600`, `100MB`) | - |
+//! | `LOG_FILE_MAX_TOTAL_BYTES` | Delete the oldest files beyond this total size (`1GB`) | - |
+//! | `LOG_FILE_MAX_AGE_SECS` | Delete files older than this many seconds | - |
+//! | `LOG_FILE_COMPRESSION` | Compression of rotated files (`none`, `gzip`, `zstd`) | `none` |
+//! | `LOG_FILE_NAME` | Appender name, used as the filename prefix if none is set | - |
+//! | `LOG_FILE_FILTER` | Directive filter in `RUST_LOG` syntax (`warn`, `audit=info`), overrides `LOG_FILE_LEVEL` | - |
+//!
+//! ### Multiple File Appenders
+//!
+//! Additional file appenders take the same variables under an indexed prefix, `LOG_FILE_0_`,
+//! `LOG_FILE_1_`, ..., or under a named prefix listed in `LOG_FILE_NAMES`. A named appender
+//! uses its name as the filename prefix by default:
+//!
+//! ```bash
+//! LOG_FILE_NAMES=app,error,audit
+//! LOG_FILE_APP_ENABLE=true
+//! LOG_FILE_ERROR_ENABLE=true
+//! LOG_FILE_ERROR_FILTER=warn
+//! LOG_FILE_AUDIT_ENABLE=true
+//! LOG_FILE_AUDIT_FILTER=audit=info
+//! LOG_FILE_AUDIT_FORMAT=json
+//! LOG_FILE_AUDIT_DIR=/var/log/audit
+//! ```
+//!
+//! ### Syslog Environment Variables
+//!
+//! | Variable | Description | Default |
+//! |----------|-------------|---------|
+//! | `LOG_SYSLOG_ENABLE` | Enable the syslog output configured by the `LOG_SYSLOG_*` variables | `true` |
+//! | `LOG_SYSLOG_TRANSPORT` | Transport (`unix`, `udp`, `tcp`) | `unix` |
+//! | `LOG_SYSLOG_ADDRESS` | Socket path or `host:port` | `/dev/log` or `127.0.0.1:514` |
+//! | `LOG_SYSLOG_FACILITY` | Facility (`user`, `daemon`, `local0` ... `local7`, ...) | `user` |
+//! | `LOG_SYSLOG_APP_NAME` | APP-NAME of the messages | Service name |
+//! | `LOG_SYSLOG_HOSTNAME` | HOSTNAME of the messages | Host name |
+//! | `LOG_SYSLOG_FORMAT` | Format of the message text | `compact` |
+//! | `LOG_SYSLOG_FILTER` | Directive filter in `RUST_LOG` syntax | - |
+//!
+//! Levels map to the syslog severities `err` (ERROR), `warning` (WARN), `info` (INFO) and
+//! `debug` (DEBUG and TRACE). The journald output uses the same mapping for `PRIORITY`.
+//!
+//! ### Journald Environment Variables
+//!
+//! | Variable | Description | Default |
+//! |----------|-------------|---------|
+//! | `LOG_JOURNALD_ENABLE` | Enable the journald output configured by the `LOG_JOURNALD_*` variables | `true` |
+//! | `LOG_JOURNALD_SOCKET` | Journal socket | `/run/systemd/journal/socket` |
+//! | `LOG_JOURNALD_SYSLOG_IDENTIFIER` | `SYSLOG_IDENTIFIER` of the entries | Service name |
+//! | `LOG_JOURNALD_FIELD_PREFIX` | Prefix of the event field names | - |
+//! | `LOG_JOURNALD_FILTER` | Directive filter in `RUST_LOG` syntax | - |
+//!
+//! ### JSON Layout Environment Variables
+//!
+//! These apply to the `json`, `ecs`, `gcp` and `datadog` formats. Use the `LOG_FILE_JSON_`
+//! prefix instead to configure the file output separately.
+//!
+//! | Variable | Description | Default |
+//! |----------|-------------|---------|
+//! | `LOG_JSON_CURRENT_SPAN` | Include the current span | `true` |
+//! | `LOG_JSON_SPAN_LIST` | Include the list of spans in scope | `true` |
+//! | `LOG_JSON_TARGET` | Include the event target | `true` |
+//! | `LOG_JSON_FILE` | Include the source file name | `false` |
+//! | `LOG_JSON_LINE_NUMBER` | Include the source line number | `false` |
+//! | `LOG_JSON_THREAD_NAMES` | Include the thread name | `false` |
+//! | `LOG_JSON_THREAD_IDS` | Include the thread id | `false` |
+//! | `LOG_JSON_FLATTEN_EVENT` | Write event fields at the top level | `true` |
+//! | `LOG_JSON_RENAMES` | Top-level key renames (`message=msg,level=severity`) | - |
+//! | `LOG_JSON_TIMESTAMP_FORMAT` | Timestamp format (`rfc3339`, `epoch_millis`) | `rfc3339` |
 //!
 //! # Examples
 //!
@@ -210,6 +335,10 @@
 //! LOG_FILE_FILENAME_PREFIX=myapp
 //! LOG_FILE_FILENAME_SUFFIX=log
 //! LOG_FILE_MAX_LOG_FILES=10
+//! # Also rotate at 100MB, compress rotated files and keep at most 1GB
+//! LOG_FILE_MAX_BYTES=100MB
+//! LOG_FILE_COMPRESSION=gzip
+//! LOG_FILE_MAX_TOTAL_BYTES=1GB
 //! ```
 //!
 //! ## Console Only Configuration
@@ -224,20 +353,63 @@
 //! LOG_FILE_ENABLE=true
 //! LOG_FILE_FORMAT=json
 //! ```
-use crate::{
-    logs::{
-        create_output_layers,
-        layer::{deserialize_attributes, deserialize_log_format, LogFormat, LogRollingRotation},
-        subscriber::setup_tracing,
+//!
+//! ## OpenTelemetry Environment Variables
+//!
+//! The standard `OTEL_SERVICE_NAME`, `OTEL_RESOURCE_ATTRIBUTES`, `OTEL_TRACES_SAMPLER`,
+//! `OTEL_TRACES_SAMPLER_ARG`, `OTEL_METRIC_EXPORT_INTERVAL`, `OTEL_TRACES_EXPORTER`,
+//! `OTEL_METRICS_EXPORTER` and `OTEL_SDK_DISABLED` are also read, with a `LOG_*` variable taking precedence over the `OTEL_*` one for the same
+//! setting. See [`otel_env`](crate::logs::otel_env) for the mapping.
+//!
+//! ## Validation
+//!
+//! Loading reports every invalid variable and out of range setting at once, in a
+//! [`ValidationError`](crate::logs::ValidationError) listing each variable or field path,
+//! for example `LOG_FORMAT` or `sample_ratio`. A `LOG_FILE_*` block which cannot be loaded,
+//! such as one with an invalid `LOG_FILE_MAX_BYTES`, is an error rather than being ignored.
+//!
+//! Unknown `LOG_*` variables, such as a misspelled `LOG_LEVL`, and the settings of file
+//! appenders not listed in `LOG_FILE_NAMES` are not errors. They are returned as warnings
+//! by [`Logger::from_env_with_warnings`], and [`init_logging_from_env`] logs them once
+//! tracing is initialized.
+#[cfg(feature = "env")]
+use crate::logs::otel_env::apply_otel_vars;
+#[cfg(feature = "env")]
+use crate::logs::validate::{
+    env_var_warnings, is_file_appender_var, ValidationError, ValidationIssue,
+};
+use crate::logs::{
+    create_output_layers,
+    guard::LoggerGuard,
+    journald::JournaldConfig,
+    json::JsonOptions,
+    layer::{
+        deserialize_attributes, deserialize_log_format, deserialize_resource_detectors,
+        deserialize_span_field_prefix, ConsoleTarget, LogFormat, LogRollingRotation,
+    },
+    logfmt::SpanFieldPrefix,
+    rolling::{deserialize_byte_size_optional, LogCompression, RollingFileWriter},
+    subscriber::{parse_filter, OutputLayers},
+    syslog::SyslogConfig,
+};
+use crate::otel::{
+    build_meter_provider, build_subscriber, build_tracer_provider_with_sampler,
+    get_resource_with_detectors, init_env_filter,
+    opentelemetry_sdk::{
+        propagation::TraceContextPropagator,
+        resource::{EnvResourceDetector, ResourceDetector},
+        trace::Sampler,
+        Resource,
     },
-    otel::OtelGuard,
+    Detector, TracingSubscriber,
 };
 use anyhow::{Context, Result};
-use opentelemetry::KeyValue;
+use opentelemetry::{global, Key, KeyValue};
 use serde::Deserialize;
+use std::{sync::OnceLock, time::Duration};
 use tracing::Level;
 use tracing_appender::rolling::Rotation;
-use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::{fmt::format::FmtSpan, util::SubscriberInitExt, EnvFilter};
 
 /// Configuration for the OpenTelemetry tracing and logging system.
 ///
@@ -289,14 +461,40 @@ pub struct Logger {
     #[serde(default = "default::service_name")]
     pub service_name: String,
 
+    /// The `service.version` resource attribute, see [`logger!`](crate::logger).
+    #[serde(default)]
+    pub service_version: Option<String>,
+
+    /// The `service.namespace` resource attribute.
+    #[serde(default)]
+    pub service_namespace: Option<String>,
+
+    /// The `service.instance.id` resource attribute.
+    /// Without one here, in `attributes` or in `OTEL_RESOURCE_ATTRIBUTES`, a random UUID is
+    /// used, generated once per process.
+    #[serde(default)]
+    pub service_instance_id: Option<String>,
+
+    /// The `deployment.environment.name` resource attribute, such as `production`. It is also
+    /// exported as the `deployment.environment` key of older semantic conventions.
+    #[serde(default)]
+    pub deployment_environment: Option<String>,
+
     /// The format to use for log output.
-    /// Supported formats: compact, pretty, json.
+    /// Supported formats: compact, pretty, json, logfmt, ecs, gcp, datadog.
     #[serde(
         deserialize_with = "deserialize_log_format",
         default = "LogFormat::default"
     )]
     pub format: LogFormat,
 
+    /// How the keys of span fields are prefixed in the logfmt output, of every output
+    /// using that format.
+    ///
+    /// * options: `none` | `span_name` | any other string used as a fixed prefix
+    #[serde(default, deserialize_with = "deserialize_span_field_prefix")]
+    pub logfmt_span_field_prefix: SpanFieldPrefix,
+
     /// The span events to include in the output.
     #[serde(
         default = "default::span_events",
@@ -322,37 +520,123 @@ pub struct Logger {
     #[serde(default = "default::sample_ratio")]
     pub sample_ratio: f64,
 
+    /// Whether to follow the sampling decision of the parent span, so that `sample_ratio`
+    /// only applies to root spans.
+    /// Defaults to true.
+    #[serde(default = "default::parent_based_sampling")]
+    pub parent_based_sampling: bool,
+
     /// The interval in seconds between metrics collection.
     /// Defaults to 30 seconds.
     #[serde(default = "default::metrics_interval_secs")]
     pub metrics_interval_secs: u64,
 
+    /// Disable the OpenTelemetry SDK: no tracer or meter provider is created and nothing is
+    /// exported, while the console, file, syslog and journald outputs keep working.
+    #[serde(default)]
+    pub sdk_disabled: bool,
+
+    /// Whether to export traces over OTLP.
+    /// Defaults to true.
+    #[serde(default = "default::export_enabled")]
+    pub traces_enabled: bool,
+
+    /// Whether to export metrics over OTLP.
+    /// Defaults to true.
+    #[serde(default = "default::export_enabled")]
+    pub metrics_enabled: bool,
+
     /// Additional attributes to add to the resource.
     /// These will be included in all traces and metrics.
     #[serde(default, deserialize_with = "deserialize_attributes")]
     pub attributes: Vec<KeyValue>,
 
+    /// The resource detectors to enable, none by default.
+    /// Explicit attributes take precedence over detected ones, see
+    /// [`get_resource_with_detectors`].
+    #[serde(default, deserialize_with = "deserialize_resource_detectors")]
+    pub resource_detectors: Vec<Detector>,
+
     /// Whether to enable console output.
     /// Defaults to true.
     #[serde(default = "default::console_enabled")]
     pub console_enabled: bool,
 
+    /// Where console output is written.
+    ///
+    /// * options: `stdout` | `stderr` | `split`
+    #[serde(default)]
+    pub console_target: ConsoleTarget,
+
+    /// Write console output from a background thread.
+    #[serde(default)]
+    pub console_non_blocking: bool,
+
+    /// The number of lines the non-blocking console writer buffers.
+    /// Defaults to 128,000.
+    #[serde(default)]
+    pub console_buffered_lines_limit: Option<usize>,
+
+    /// Drop lines when the non-blocking console buffer is full, instead of blocking.
+    /// Defaults to true.
+    #[serde(default = "default::lossy")]
+    pub console_lossy: bool,
+
     /// Set this if you want to write log to file
     #[serde(default)]
     pub file_appender: Option<LoggerFileAppender>,
+
+    /// Additional file appenders, each with its own filter, format, rotation and directory.
+    #[serde(default)]
+    pub file_appenders: Vec<LoggerFileAppender>,
+
+    /// Send logs to the local syslog daemon or a remote syslog server.
+    #[serde(default)]
+    pub syslog: Option<SyslogConfig>,
+
+    /// Send logs to systemd-journald.
+    #[serde(default)]
+    pub journald: Option<JournaldConfig>,
+
+    /// Layout options for the JSON formats.
+    #[serde(default)]
+    pub json: JsonOptions,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct LoggerFileAppender {
     /// Enable logger file appender
+    /// Defaults to true, so that setting any of its fields is enough to enable it.
+    #[serde(default = "default::enable")]
     pub enable: bool,
 
+    /// The name of the appender, used as the filename prefix if none is set.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// A directive filter for this appender in `RUST_LOG` syntax, such as `warn` or
+    /// `audit=info`. Takes precedence over `level`.
+    ///
+    /// It applies on top of the global filter, so it can only narrow what is recorded.
+    #[serde(default)]
+    pub filter: Option<String>,
+
     /// Enable write log to file non-blocking
     #[serde(default)]
     pub non_blocking: bool,
 
+    /// The number of lines the non-blocking writer buffers.
+    /// Defaults to 128,000.
+    #[serde(default)]
+    pub buffered_lines_limit: Option<usize>,
+
+    /// Drop lines when the non-blocking buffer is full, instead of blocking.
+    /// Defaults to true.
+    #[serde(default = "default::lossy")]
+    pub lossy: bool,
+
     /// The minimum log level to record.
-    /// If not set, will use the level from Logger
+    /// If not set, only the global filter of the Logger applies
     #[serde(default, deserialize_with = "deserialize_level_optional")]
     pub level: Option<Level>,
 
@@ -363,7 +647,7 @@ pub struct LoggerFileAppender {
     /// Set the logger file appender format.
     /// If not set, will use the format from Logger
     ///
-    /// * options: `compact` | `pretty` | `json`
+    /// * options: `compact` | `pretty` | `json` | `logfmt` | `ecs` | `gcp` | `datadog`
     #[serde(default, deserialize_with = "deserialize_log_format_optional")]
     pub format: Option<LogFormat>,
 
@@ -388,29 +672,137 @@ pub struct LoggerFileAppender {
     /// Set the logger file appender keep max log files.
     #[serde(default = "default::max_log_files")]
     pub max_log_files: usize,
+
+    /// Rotate the current file once it reaches this size, in addition to the rotation.
+    /// Accepts a number of bytes or a size such as `100MB`.
+    #[serde(default, deserialize_with = "deserialize_byte_size_optional")]
+    pub max_bytes: Option<u64>,
+
+    /// Delete the oldest log files once all of them together exceed this size.
+    /// Accepts a number of bytes or a size such as `1GB`.
+    #[serde(default, deserialize_with = "deserialize_byte_size_optional")]
+    pub max_total_bytes: Option<u64>,
+
+    /// Delete log files last modified more than this many seconds ago.
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+
+    /// Set the compression of rotated log files.
+    ///
+    /// * options: `none` | `gzip` | `zstd`
+    #[serde(default)]
+    pub compression: LogCompression,
+
+    /// Layout options for the JSON formats.
+    /// If not set, will use the options from Logger
+    #[serde(default)]
+    pub json: Option<JsonOptions>,
+}
+
+impl Default for LoggerFileAppender {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            name: None,
+            filter: None,
+            non_blocking: false,
+            buffered_lines_limit: None,
+            lossy: default::lossy(),
+            level: None,
+            ansi: false,
+            format: None,
+            rotation: default::rotation(),
+            dir: None,
+            filename_prefix: None,
+            filename_suffix: None,
+            max_log_files: default::max_log_files(),
+            max_bytes: None,
+            max_total_bytes: None,
+            max_age_secs: None,
+            compression: LogCompression::None,
+            json: None,
+        }
+    }
 }
 
 impl LoggerFileAppender {
+    /// Create an enabled file appender with the given name, which is also the filename prefix.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Set the directive filter, such as `warn` or `audit=info`.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Set the log format.
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Set the time-based rotation.
+    pub fn with_rotation(mut self, rotation: LogRollingRotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Set the log directory.
+    pub fn with_dir(mut self, dir: impl Into<String>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Set whether to write from a background thread.
+    pub fn with_non_blocking(mut self, non_blocking: bool) -> Self {
+        self.non_blocking = non_blocking;
+        self
+    }
+
+    /// Set the number of lines the non-blocking writer buffers.
+    pub fn with_buffered_lines_limit(mut self, limit: usize) -> Self {
+        self.buffered_lines_limit = Some(limit);
+        self
+    }
+
+    /// Set whether the non-blocking writer drops lines when its buffer is full,
+    /// or blocks until there is room.
+    pub fn with_lossy(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
+    }
+
     /// Merge configuration from Logger, using LoggerFileAppender values if set,
     /// otherwise fall back to Logger values
     pub fn merge_with_logger(&self, logger: &Logger) -> LoggerFileAppender {
         LoggerFileAppender {
             enable: self.enable,
+            name: self.name.clone(),
+            filter: self.filter.clone(),
             ansi: self.ansi,
             non_blocking: self.non_blocking,
-            level: self.level.or(Some(logger.level)),
+            buffered_lines_limit: self.buffered_lines_limit,
+            lossy: self.lossy,
+            level: self.level,
             format: self.format.clone().or(Some(logger.format.clone())),
             rotation: self.rotation.clone(),
             dir: self.dir.clone().or(Some(default::dir())),
-            filename_prefix: self
-                .filename_prefix
-                .clone()
-                .or(Some(default::filename_prefix())),
+            filename_prefix: Some(self.filename_prefix_or_default()),
             filename_suffix: self
                 .filename_suffix
                 .clone()
                 .or(Some(default::filename_suffix())),
             max_log_files: self.max_log_files,
+            max_bytes: self.max_bytes,
+            max_total_bytes: self.max_total_bytes,
+            max_age_secs: self.max_age_secs,
+            compression: self.compression,
+            json: self.json.clone().or(Some(logger.json.clone())),
         }
     }
 
@@ -421,6 +813,7 @@ impl LoggerFileAppender {
     pub fn filename_prefix_or_default(&self) -> String {
         self.filename_prefix
             .clone()
+            .or_else(|| self.name.clone())
             .unwrap_or_else(default::filename_prefix)
     }
 
@@ -434,6 +827,35 @@ impl LoggerFileAppender {
         self.format.clone().unwrap_or(LogFormat::Compact)
     }
 
+    /// Build the per-appender filter from `filter`, or from `level` if no filter is set.
+    pub fn build_filter(&self) -> Result<Option<EnvFilter>> {
+        match (&self.filter, self.level) {
+            (Some(filter), _) => parse_filter(filter).map(Some),
+            (None, Some(level)) => Ok(Some(EnvFilter::new(level.to_string()))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Build the rolling file writer for this configuration.
+    pub fn build_writer(&self) -> Result<RollingFileWriter> {
+        let mut builder = RollingFileWriter::builder()
+            .rotation(self.rotation.clone())
+            .filename_prefix(self.filename_prefix_or_default())
+            .filename_suffix(self.filename_suffix_or_default())
+            .max_log_files(self.max_log_files)
+            .compression(self.compression);
+        if let Some(max_bytes) = self.max_bytes {
+            builder = builder.max_bytes(max_bytes);
+        }
+        if let Some(max_total_bytes) = self.max_total_bytes {
+            builder = builder.max_total_bytes(max_total_bytes);
+        }
+        if let Some(max_age_secs) = self.max_age_secs {
+            builder = builder.max_age(Duration::from_secs(max_age_secs));
+        }
+        builder.build(self.dir_or_default())
+    }
+
     pub fn get_rolling_rotation(&self) -> Rotation {
         match self.rotation {
             LogRollingRotation::Minutely => Rotation::MINUTELY,
@@ -496,7 +918,9 @@ where
     s.parse().map(Some).map_err(serde::de::Error::custom)
 }
 
-fn deserialize_log_format_optional<'de, D>(deserializer: D) -> Result<Option<LogFormat>, D::Error>
+pub(crate) fn deserialize_log_format_optional<'de, D>(
+    deserializer: D,
+) -> Result<Option<LogFormat>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -508,6 +932,10 @@ where
         "compact" => Ok(Some(LogFormat::Compact)),
         "pretty" => Ok(Some(LogFormat::Pretty)),
         "json" => Ok(Some(LogFormat::Json)),
+        "logfmt" => Ok(Some(LogFormat::Logfmt)),
+        "ecs" => Ok(Some(LogFormat::Ecs)),
+        "gcp" => Ok(Some(LogFormat::Gcp)),
+        "datadog" => Ok(Some(LogFormat::Datadog)),
         _ => Err(serde::de::Error::custom(format!(
             "Invalid log format: '{s}'"
         ))),
@@ -557,6 +985,10 @@ pub mod default {
         1.0
     }
 
+    pub fn parent_based_sampling() -> bool {
+        true
+    }
+
     pub fn metrics_interval_secs() -> u64 {
         30
     }
@@ -569,21 +1001,54 @@ pub mod default {
     pub fn console_enabled() -> bool {
         true
     }
+
+    /// Default enable of a configured file appender, syslog or journald output: true
+    pub fn enable() -> bool {
+        true
+    }
+
+    /// Default traces and metrics export: true
+    pub fn export_enabled() -> bool {
+        true
+    }
+
+    /// Default non-blocking policy: drop lines when the buffer is full
+    pub fn lossy() -> bool {
+        true
+    }
 }
 
 impl Default for Logger {
     fn default() -> Self {
         Self {
             service_name: default::service_name(),
+            service_version: None,
+            service_namespace: None,
+            service_instance_id: None,
+            deployment_environment: None,
             format: LogFormat::default(),
+            logfmt_span_field_prefix: SpanFieldPrefix::default(),
             span_events: default::span_events(),
             ansi: true,
             level: default::log_level(),
             sample_ratio: default::sample_ratio(),
+            parent_based_sampling: default::parent_based_sampling(),
             metrics_interval_secs: default::metrics_interval_secs(),
+            sdk_disabled: false,
+            traces_enabled: default::export_enabled(),
+            metrics_enabled: default::export_enabled(),
             attributes: vec![],
+            resource_detectors: Vec::new(),
             console_enabled: default::console_enabled(),
+            console_target: ConsoleTarget::default(),
+            console_non_blocking: false,
+            console_buffered_lines_limit: None,
+            console_lossy: default::lossy(),
             file_appender: None,
+            file_appenders: Vec::new(),
+            syslog: None,
+            journald: None,
+            json: JsonOptions::default(),
         }
     }
 }
@@ -607,12 +1072,73 @@ impl Logger {
         self
     }
 
-    /// Set the log format (compact, pretty, or json).
+    /// Set the `service.version` resource attribute.
+    pub fn with_service_version(mut self, version: impl Into<String>) -> Self {
+        self.service_version = Some(version.into());
+        self
+    }
+
+    /// Set the `service.namespace` resource attribute.
+    pub fn with_service_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.service_namespace = Some(namespace.into());
+        self
+    }
+
+    /// Set the `service.instance.id` resource attribute, instead of a random UUID.
+    pub fn with_service_instance_id(mut self, instance_id: impl Into<String>) -> Self {
+        self.service_instance_id = Some(instance_id.into());
+        self
+    }
+
+    /// Set the `deployment.environment.name` and `deployment.environment` resource attributes.
+    pub fn with_deployment_environment(mut self, environment: impl Into<String>) -> Self {
+        self.deployment_environment = Some(environment.into());
+        self
+    }
+
+    /// The resource attributes: the `attributes`, overridden by the service version,
+    /// namespace, instance ID and deployment environment.
+    pub fn resource_attributes(&self) -> Vec<KeyValue> {
+        let mut attributes = self.attributes.clone();
+        let semantic = [
+            ("service.version", &self.service_version),
+            ("service.namespace", &self.service_namespace),
+            ("service.instance.id", &self.service_instance_id),
+            ("deployment.environment.name", &self.deployment_environment),
+            // The key before semantic conventions 1.27, still read by some backends
+            ("deployment.environment", &self.deployment_environment),
+        ];
+        for (key, value) in semantic {
+            if let Some(value) = value {
+                attributes.retain(|attribute| attribute.key.as_str() != key);
+                attributes.push(KeyValue::new(key, value.clone()));
+            }
+        }
+        attributes
+    }
+
+    // The sampler of the sample ratio, following the parent span if parent-based
+    fn sampler(&self) -> Sampler {
+        let sampler = Sampler::TraceIdRatioBased(self.sample_ratio);
+        if self.parent_based_sampling {
+            Sampler::ParentBased(Box::new(sampler))
+        } else {
+            sampler
+        }
+    }
+
+    /// Set the log format (compact, pretty, json, logfmt, ecs, gcp or datadog).
     pub fn with_format(mut self, format: LogFormat) -> Self {
         self.format = format;
         self
     }
 
+    /// Set how the keys of span fields are prefixed in the logfmt output.
+    pub fn with_logfmt_span_field_prefix(mut self, prefix: SpanFieldPrefix) -> Self {
+        self.logfmt_span_field_prefix = prefix;
+        self
+    }
+
     /// Set the span events to include in the output.
     pub fn with_span_events(mut self, span_events: FmtSpan) -> Self {
         self.span_events = span_events;
@@ -637,18 +1163,48 @@ impl Logger {
         self
     }
 
+    /// Set whether to follow the sampling decision of the parent span. Defaults to true.
+    pub fn with_parent_based_sampling(mut self, parent_based: bool) -> Self {
+        self.parent_based_sampling = parent_based;
+        self
+    }
+
     /// Set the interval in seconds between metrics collection.
     pub fn with_metrics_interval_secs(mut self, secs: u64) -> Self {
         self.metrics_interval_secs = secs;
         self
     }
 
+    /// Set whether to disable the OpenTelemetry SDK, keeping only the log outputs.
+    pub fn with_sdk_disabled(mut self, disabled: bool) -> Self {
+        self.sdk_disabled = disabled;
+        self
+    }
+
+    /// Set whether to export traces over OTLP.
+    pub fn with_traces_enabled(mut self, enabled: bool) -> Self {
+        self.traces_enabled = enabled;
+        self
+    }
+
+    /// Set whether to export metrics over OTLP.
+    pub fn with_metrics_enabled(mut self, enabled: bool) -> Self {
+        self.metrics_enabled = enabled;
+        self
+    }
+
     /// Add custom attributes to the resource.
     pub fn with_attributes(mut self, attributes: Vec<KeyValue>) -> Self {
         self.attributes = attributes;
         self
     }
 
+    /// Set the resource detectors, such as [`Detector::Host`] or [`Detector::K8s`].
+    pub fn with_resource_detectors(mut self, detectors: Vec<Detector>) -> Self {
+        self.resource_detectors = detectors;
+        self
+    }
+
     /// Set whether to enable console output.
     ///
     /// # Arguments
@@ -673,12 +1229,113 @@ impl Logger {
         self
     }
 
+    /// Set where console output is written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::{logs::ConsoleTarget, Logger};
+    ///
+    /// // WARN and ERROR go to stderr, everything else to stdout
+    /// let logger = Logger::new("my-service").with_console_target(ConsoleTarget::Split);
+    /// ```
+    pub fn with_console_target(mut self, target: ConsoleTarget) -> Self {
+        self.console_target = target;
+        self
+    }
+
+    /// Set whether to write console output from a background thread.
+    pub fn with_console_non_blocking(mut self, non_blocking: bool) -> Self {
+        self.console_non_blocking = non_blocking;
+        self
+    }
+
+    /// Set the number of lines the non-blocking console writer buffers.
+    pub fn with_console_buffered_lines_limit(mut self, limit: usize) -> Self {
+        self.console_buffered_lines_limit = Some(limit);
+        self
+    }
+
+    /// Set whether the non-blocking console writer drops lines when its buffer is full,
+    /// or blocks until there is room.
+    pub fn with_console_lossy(mut self, lossy: bool) -> Self {
+        self.console_lossy = lossy;
+        self
+    }
+
     /// Set file appender configuration.
     pub fn with_file_appender(mut self, file_appender: Option<LoggerFileAppender>) -> Self {
         self.file_appender = file_appender;
         self
     }
 
+    /// Set additional file appenders, written to alongside `file_appender`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::{Logger, LoggerFileAppender};
+    ///
+    /// let logger = Logger::new("my-service").with_file_appenders(vec![
+    ///     LoggerFileAppender::new("app").with_filter("info"),
+    ///     LoggerFileAppender::new("error").with_filter("warn"),
+    ///     LoggerFileAppender::new("audit").with_filter("audit=info"),
+    /// ]);
+    /// ```
+    pub fn with_file_appenders(mut self, file_appenders: Vec<LoggerFileAppender>) -> Self {
+        self.file_appenders = file_appenders;
+        self
+    }
+
+    /// Set the syslog output configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::{logs::{SyslogConfig, SyslogFacility, SyslogTransport}, Logger};
+    ///
+    /// let logger = Logger::new("my-service").with_syslog(Some(
+    ///     SyslogConfig::new(SyslogTransport::Udp)
+    ///         .with_address("10.0.0.1:514")
+    ///         .with_facility(SyslogFacility::Local0),
+    /// ));
+    /// ```
+    pub fn with_syslog(mut self, syslog: Option<SyslogConfig>) -> Self {
+        self.syslog = syslog;
+        self
+    }
+
+    /// Set the journald output configuration.
+    pub fn with_journald(mut self, journald: Option<JournaldConfig>) -> Self {
+        self.journald = journald;
+        self
+    }
+
+    /// All configured file appenders, `file_appender` first.
+    pub fn all_file_appenders(&self) -> impl Iterator<Item = &LoggerFileAppender> {
+        self.file_appender.iter().chain(&self.file_appenders)
+    }
+
+    /// Set the layout options for the JSON formats.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::{logs::{JsonOptions, TimestampFormat}, LogFormat, Logger};
+    ///
+    /// let logger = Logger::new("my-service")
+    ///     .with_format(LogFormat::Json)
+    ///     .with_json_options(
+    ///         JsonOptions::default()
+    ///             .with_span_list(false)
+    ///             .with_timestamp_format(TimestampFormat::EpochMillis),
+    ///     );
+    /// ```
+    pub fn with_json_options(mut self, json: JsonOptions) -> Self {
+        self.json = json;
+        self
+    }
+
     /// Initialize tracing with this configuration.
     ///
     /// This method will:
@@ -696,8 +1353,9 @@ impl Logger {
     ///
     /// # Returns
     ///
-    /// Returns a `Result` containing a `ProviderGuard` that will automatically
-    /// clean up the tracing providers when dropped.
+    /// Returns a `Result` containing a [`LoggerGuard`] that will automatically
+    /// clean up the tracing providers and flush the non-blocking writers when dropped.
+    /// Its [`LoggerGuard::dropped_lines`] reports the lines the non-blocking writers lost.
     ///
     /// # Examples
     ///
@@ -719,14 +1377,18 @@ impl Logger {
     ///
     /// File-only logging:
     /// ```rust
-    /// use tracing_otel_extra::{Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
+    /// use tracing_otel_extra::{LogCompression, Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
     /// use tracing::Level;
     ///
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let file_appender = LoggerFileAppender {
     ///         enable: true,
+    ///         name: None,
+    ///         filter: None,
     ///         non_blocking: false,
+    ///         buffered_lines_limit: None,
+    ///         lossy: true,
     ///         level: Some(Level::INFO),
     ///         ansi: false,
     ///         format: Some(LogFormat::Json),
@@ -735,6 +1397,11 @@ impl Logger {
     ///         filename_prefix: Some("app".to_string()),
     ///         filename_suffix: Some("log".to_string()),
     ///         max_log_files: 5,
+    ///         max_bytes: None,
+    ///         max_total_bytes: None,
+    ///         max_age_secs: None,
+    ///         compression: LogCompression::None,
+    ///         json: None,
     ///     };
     ///
     ///     let guard = Logger::new("my-service")
@@ -751,14 +1418,18 @@ impl Logger {
     ///
     /// Both console and file logging:
     /// ```rust
-    /// use tracing_otel_extra::{Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
+    /// use tracing_otel_extra::{LogCompression, Logger, LoggerFileAppender, LogFormat, LogRollingRotation};
     /// use tracing::Level;
     ///
     /// #[tokio::main]
     /// async fn main() -> anyhow::Result<()> {
     ///     let file_appender = LoggerFileAppender {
     ///         enable: true,
+    ///         name: None,
+    ///         filter: None,
     ///         non_blocking: false,
+    ///         buffered_lines_limit: None,
+    ///         lossy: true,
     ///         level: Some(Level::INFO),
     ///         ansi: false,
     ///         format: Some(LogFormat::Json),
@@ -767,6 +1438,11 @@ impl Logger {
     ///         filename_prefix: Some("app".to_string()),
     ///         filename_suffix: Some("log".to_string()),
     ///         max_log_files: 5,
+    ///         max_bytes: None,
+    ///         max_total_bytes: None,
+    ///         max_age_secs: None,
+    ///         compression: LogCompression::None,
+    ///         json: None,
     ///     };
     ///
     ///     let guard = Logger::new("my-service")
@@ -784,14 +1460,44 @@ impl Logger {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The configuration is invalid, see [`Logger::validate`]
     /// - Failed to initialize the tracing subscriber
     /// - Failed to set up OpenTelemetry providers
     /// - Failed to configure the environment filter
     /// - No output layers are configured (both console and file are disabled)
-    pub fn init(self) -> Result<OtelGuard> {
+    pub fn init(self) -> Result<LoggerGuard> {
         init_tracing_from_logger(self)
     }
 
+    /// Initialize the logger like [`Logger::init`], but return an error instead of panicking
+    /// if a global default subscriber has already been set, for example by a host
+    /// application or an earlier test.
+    pub fn try_init(self) -> Result<LoggerGuard> {
+        try_init_tracing_from_logger(self)
+    }
+
+    /// Build the subscriber and its guard without installing the subscriber globally.
+    ///
+    /// The subscriber can be scoped with [`tracing::subscriber::with_default`] or
+    /// [`tracing::subscriber::set_default`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::Logger;
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let (subscriber, _guard) = Logger::new("my-service").with_sdk_disabled(true).build()?;
+    ///     tracing::subscriber::with_default(subscriber, || {
+    ///         tracing::info!("only recorded by this subscriber");
+    ///     });
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build(self) -> Result<(TracingSubscriber, LoggerGuard)> {
+        build_tracing_from_logger(self)
+    }
+
     /// Initialize the logger from environment variables.
     ///
     /// This method requires the "env" feature to be enabled.
@@ -818,52 +1524,400 @@ impl Logger {
     pub fn from_env(prefix: Option<&str>) -> Result<Self> {
         init_logger_from_env(prefix)
     }
+
+    /// Load the logger from environment variables like [`Logger::from_env`], along with the
+    /// warnings about the variables it ignored, such as a misspelled `LOG_LEVL`.
+    ///
+    /// The warnings can only be logged once tracing is initialized.
+    /// [`init_logging_from_env`] does this.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::Logger;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     #[cfg(feature = "env")]
+    ///     {
+    ///         let (logger, warnings) = Logger::from_env_with_warnings(None)?;
+    ///         let guard = logger.init()?;
+    ///         for warning in warnings {
+    ///             tracing::warn!("{warning}");
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "env")]
+    pub fn from_env_with_warnings(prefix: Option<&str>) -> Result<(Self, Vec<String>)> {
+        let vars: Vec<(String, String)> = std::env::vars().collect();
+        logger_from_vars(prefix.unwrap_or("LOG_"), &vars)
+    }
+}
+
+/// Create a [`Logger`] with the calling package's version as `service.version`.
+///
+/// Without a service name, the package name is used.
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing_otel_extra::logger;
+///
+/// let logger = logger!("my-service");
+/// assert_eq!(logger.service_version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+/// ```
+#[macro_export]
+macro_rules! logger {
+    () => {
+        $crate::logger!(env!("CARGO_PKG_NAME"))
+    };
+    ($service_name:expr) => {
+        $crate::Logger::new($service_name).with_service_version(env!("CARGO_PKG_VERSION"))
+    };
 }
 
 // Initialize tracing from logger
-pub fn init_tracing_from_logger(logger: Logger) -> Result<OtelGuard> {
-    let layers = create_output_layers(&logger)?;
+pub fn init_tracing_from_logger(logger: Logger) -> Result<LoggerGuard> {
+    let (subscriber, guard) = build_tracing_from_logger(logger)?;
+    subscriber.init();
+    set_global_providers(&guard);
+    Ok(guard)
+}
+
+/// Like [`init_tracing_from_logger`], but returns an error instead of panicking if a global
+/// default subscriber has already been set.
+pub fn try_init_tracing_from_logger(logger: Logger) -> Result<LoggerGuard> {
+    // checked first, so that no exporter is started for nothing
+    if tracing::dispatcher::has_been_set() {
+        anyhow::bail!(
+            "Failed to initialize tracing: a global default subscriber has already been set"
+        );
+    }
+    let (subscriber, guard) = build_tracing_from_logger(logger)?;
+    subscriber
+        .try_init()
+        .context("Failed to set the global default subscriber")?;
+    set_global_providers(&guard);
+    Ok(guard)
+}
 
-    let guard = setup_tracing(
+/// Compose the subscriber for the logger without installing it.
+///
+/// The OpenTelemetry providers whose export is enabled are created, but not registered as
+/// the global tracer and meter providers, which only `init` and `try_init` do. Without any,
+/// no exporter or background task is started.
+pub fn build_tracing_from_logger(logger: Logger) -> Result<(TracingSubscriber, LoggerGuard)> {
+    logger.validate()?;
+    let OutputLayers { layers, guards } = create_output_layers(&logger)?;
+
+    let traces_enabled = logger.traces_enabled && !logger.sdk_disabled;
+    let metrics_enabled = logger.metrics_enabled && !logger.sdk_disabled;
+    let resource = (traces_enabled || metrics_enabled).then(|| {
+        let mut attributes = logger.resource_attributes();
+        if !has_service_instance_id(&attributes) {
+            attributes.extend(default_service_instance_id(
+                &EnvResourceDetector::new().detect(),
+            ));
+        }
+        get_resource_with_detectors(
+            &logger.service_name,
+            &attributes,
+            &logger.resource_detectors,
+        )
+    });
+    let tracer_provider = resource
+        .as_ref()
+        .filter(|_| traces_enabled)
+        .map(|resource| build_tracer_provider_with_sampler(resource, logger.sampler()))
+        .transpose()
+        .context("Failed to initialize tracing")?;
+    let meter_provider = resource
+        .as_ref()
+        .filter(|_| metrics_enabled)
+        .map(|resource| build_meter_provider(resource, logger.metrics_interval_secs))
+        .transpose()
+        .context("Failed to initialize tracing")?;
+
+    let (subscriber, guard) = build_subscriber(
         &logger.service_name,
-        &logger.attributes,
-        logger.sample_ratio,
-        logger.metrics_interval_secs,
-        logger.level,
+        init_env_filter(&logger.level),
         layers,
-    )
-    .context("Failed to initialize tracing")?;
-    Ok(guard)
+        tracer_provider,
+        meter_provider,
+    );
+    let guard = LoggerGuard::new(guard, guards);
+    if let Some(meter_provider) = guard.meter_provider() {
+        guard.register_dropped_lines_counter(meter_provider);
+    }
+    Ok((subscriber, guard))
+}
+
+fn has_service_instance_id(attributes: &[KeyValue]) -> bool {
+    attributes
+        .iter()
+        .any(|attribute| attribute.key.as_str() == "service.instance.id")
+}
+
+// A random `service.instance.id`, the same for every logger of the process, unless the
+// resource from `OTEL_RESOURCE_ATTRIBUTES` sets one
+fn default_service_instance_id(env: &Resource) -> Option<KeyValue> {
+    static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+    let key = Key::from_static_str("service.instance.id");
+    if env.get(&key).is_some() {
+        return None;
+    }
+    let instance_id = INSTANCE_ID.get_or_init(|| uuid::Uuid::new_v4().to_string());
+    Some(KeyValue::new(key, instance_id.clone()))
+}
+
+// Register the providers of an installed subscriber as the global ones, with the W3C trace
+// context propagator alongside the tracer provider
+fn set_global_providers(guard: &LoggerGuard) {
+    if let Some(tracer_provider) = guard.tracer_provider() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        global::set_tracer_provider(tracer_provider.clone());
+    }
+    if let Some(meter_provider) = guard.meter_provider() {
+        global::set_meter_provider(meter_provider.clone());
+    }
 }
 
 /// Convenience function to initialize tracing with default settings
-pub fn init_logging(service_name: &str) -> Result<OtelGuard> {
+pub fn init_logging(service_name: &str) -> Result<LoggerGuard> {
     let logger = Logger::new(service_name);
     init_tracing_from_logger(logger)
 }
 
 #[cfg(feature = "env")]
 pub fn init_logger_from_env(prefix: Option<&str>) -> Result<Logger> {
-    let prefix = prefix.unwrap_or("LOG_");
+    Logger::from_env_with_warnings(prefix).map(|(logger, _)| logger)
+}
+
+/// Load the logger from the `{prefix}*` variables, reporting every invalid variable and
+/// setting in one [`ValidationError`], along with the warnings about ignored variables.
+#[cfg(feature = "env")]
+fn logger_from_vars(prefix: &str, vars: &[(String, String)]) -> Result<(Logger, Vec<String>)> {
     let file_prefix = format!("{prefix}FILE_");
-    // file appender from env
-    let file_appender: Option<LoggerFileAppender> = envy::prefixed(&file_prefix).from_env().ok();
+    let mut issues = Vec::new();
+    let mut warnings = Vec::new();
     // logger from env
-    let mut logger: Logger = envy::prefixed(prefix)
-        .from_env()
-        .context("Failed to deserialize environment variables")?;
+    let mut logger: Logger = deserialize_vars(prefix, vars, &mut issues).unwrap_or_default();
+    // standard OTEL_* variables, for the fields without a {prefix}* variable
+    apply_otel_vars(&mut logger, prefix, vars, &mut warnings);
+    // JSON options from env
+    logger.json =
+        deserialize_vars(&format!("{prefix}JSON_"), vars, &mut issues).unwrap_or_default();
+
+    // file appender from env, if any of its variables is set
+    let has_file_appender = vars.iter().any(|(key, _)| {
+        key.strip_prefix(&file_prefix)
+            .is_some_and(is_file_appender_var)
+    });
+    if has_file_appender {
+        if let Some(mut file_appender) =
+            deserialize_vars::<LoggerFileAppender>(&file_prefix, vars, &mut issues)
+        {
+            collect_issues(
+                file_json_options_from_vars(&mut file_appender, &file_prefix, vars),
+                &mut issues,
+            );
+            let merged_file_appender = file_appender.merge_with_logger(&logger);
+            logger = logger.with_file_appender(Some(merged_file_appender));
+        }
+    }
 
-    if let Some(file_appender) = file_appender {
-        let merged_file_appender = file_appender.merge_with_logger(&logger);
-        logger = logger.with_file_appender(Some(merged_file_appender));
+    let file_appenders = collect_issues(file_appenders_from_vars(&file_prefix, vars), &mut issues)
+        .unwrap_or_default()
+        .iter()
+        .map(|file_appender| file_appender.merge_with_logger(&logger))
+        .collect();
+    logger = logger.with_file_appenders(file_appenders);
+
+    // syslog and journald outputs from env
+    logger.syslog = collect_issues(
+        sink_from_vars(&format!("{prefix}SYSLOG_"), vars),
+        &mut issues,
+    )
+    .flatten();
+    logger.journald = collect_issues(
+        sink_from_vars(&format!("{prefix}JOURNALD_"), vars),
+        &mut issues,
+    )
+    .flatten();
+
+    warnings.extend(env_var_warnings(prefix, vars));
+    if let Err(err) = logger.validate() {
+        issues.extend(err.issues);
     }
-    Ok(logger)
+    ValidationError::check(issues)?;
+    Ok((logger, warnings))
 }
 
+/// Deserialize `T` from the `{prefix}*` variables, adding an issue for each invalid one.
+///
+/// The value is still returned, deserialized from the valid variables, so that the
+/// remaining settings can be validated as well.
 #[cfg(feature = "env")]
-pub fn init_logging_from_env(prefix: Option<&str>) -> Result<OtelGuard> {
-    let logger = init_logger_from_env(prefix)?;
-    init_tracing_from_logger(logger)
+fn deserialize_vars<T: serde::de::DeserializeOwned>(
+    prefix: &str,
+    vars: &[(String, String)],
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<T> {
+    let block: Vec<(String, String)> = vars
+        .iter()
+        .filter(|(key, _)| key.starts_with(prefix))
+        .cloned()
+        .collect();
+    let parse = |vars: Vec<(String, String)>| envy::prefixed(prefix).from_iter::<_, T>(vars);
+    if let Ok(value) = parse(block.clone()) {
+        return Some(value);
+    }
+
+    // Check each variable alone, along with `{prefix}ENABLE` which decides if the output is used
+    let (enable, others): (Vec<_>, Vec<_>) = block
+        .into_iter()
+        .partition(|(key, _)| key.strip_prefix(prefix) == Some("ENABLE"));
+    if let Err(err) = parse(enable.clone()) {
+        let path = match enable.first() {
+            Some((key, _)) => key.clone(),
+            None => format!("{prefix}*"),
+        };
+        issues.push(ValidationIssue::new(path, err.to_string()));
+        return None;
+    }
+    let mut valid = enable.clone();
+    for var in others {
+        let mut single = enable.clone();
+        single.push(var.clone());
+        match parse(single) {
+            Ok(_) => valid.push(var),
+            Err(err) => issues.push(ValidationIssue::new(var.0, err.to_string())),
+        }
+    }
+    parse(valid).ok()
+}
+
+// Add the issues of a failed load, keeping the value of a successful one
+#[cfg(feature = "env")]
+fn collect_issues<T>(result: Result<T>, issues: &mut Vec<ValidationIssue>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            match err.downcast::<ValidationError>() {
+                Ok(err) => issues.extend(err.issues),
+                Err(err) => issues.push(ValidationIssue::new("environment", format!("{err:#}"))),
+            }
+            None
+        }
+    }
+}
+
+// Load an output configuration from `{prefix}*` if any of these variables is set
+#[cfg(feature = "env")]
+fn sink_from_vars<T: serde::de::DeserializeOwned>(
+    prefix: &str,
+    vars: &[(String, String)],
+) -> Result<Option<T>> {
+    if !vars.iter().any(|(key, _)| key.starts_with(prefix)) {
+        return Ok(None);
+    }
+    let mut issues = Vec::new();
+    let sink = deserialize_vars(prefix, vars, &mut issues);
+    ValidationError::check(issues)?;
+    Ok(sink)
+}
+
+/// Load the additional file appenders, from the indexed prefixes `{file_prefix}0_`,
+/// `{file_prefix}1_`, ... followed by the named prefixes listed in `{file_prefix}NAMES`.
+#[cfg(feature = "env")]
+fn file_appenders_from_vars(
+    file_prefix: &str,
+    vars: &[(String, String)],
+) -> Result<Vec<LoggerFileAppender>> {
+    let mut indexes: Vec<u32> = vars
+        .iter()
+        .filter_map(|(key, _)| {
+            let (index, _) = key.strip_prefix(file_prefix)?.split_once('_')?;
+            index.parse().ok()
+        })
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    let names = vars
+        .iter()
+        .find(|(key, _)| key.strip_prefix(file_prefix) == Some("NAMES"))
+        .map(|(_, names)| names.as_str())
+        .unwrap_or_default();
+    let names = names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+
+    let prefixes = indexes
+        .into_iter()
+        .map(|index| (format!("{file_prefix}{index}_"), None))
+        .chain(names.map(|name| {
+            let prefix = format!("{file_prefix}{}_", name.to_uppercase());
+            (prefix, Some(name.to_lowercase()))
+        }));
+
+    let mut issues = Vec::new();
+    let mut file_appenders = Vec::new();
+    for (prefix, name) in prefixes {
+        // A listed name is more likely a typo than an appender left to its defaults
+        if !vars.iter().any(|(key, _)| key.starts_with(&prefix)) {
+            issues.push(ValidationIssue::new(
+                format!("{prefix}*"),
+                format!("no variable is set for `{}`", name.unwrap_or_default()),
+            ));
+            continue;
+        }
+        let Some(mut file_appender) =
+            deserialize_vars::<LoggerFileAppender>(&prefix, vars, &mut issues)
+        else {
+            continue;
+        };
+        file_appender.name = file_appender.name.or(name);
+        collect_issues(
+            file_json_options_from_vars(&mut file_appender, &prefix, vars),
+            &mut issues,
+        );
+        file_appenders.push(file_appender);
+    }
+    ValidationError::check(issues)?;
+    Ok(file_appenders)
+}
+
+// Load the JSON options of a file appender from `{file_prefix}JSON_`, if any is set
+#[cfg(feature = "env")]
+fn file_json_options_from_vars(
+    file_appender: &mut LoggerFileAppender,
+    file_prefix: &str,
+    vars: &[(String, String)],
+) -> Result<()> {
+    let file_json_prefix = format!("{file_prefix}JSON_");
+    if vars
+        .iter()
+        .any(|(key, _)| key.starts_with(&file_json_prefix))
+    {
+        let mut issues = Vec::new();
+        file_appender.json = deserialize_vars(&file_json_prefix, vars, &mut issues);
+        ValidationError::check(issues)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "env")]
+pub fn init_logging_from_env(prefix: Option<&str>) -> Result<LoggerGuard> {
+    let (logger, warnings) = Logger::from_env_with_warnings(prefix)?;
+    let guard = init_tracing_from_logger(logger)?;
+    for warning in warnings {
+        tracing::warn!("{warning}");
+    }
+    Ok(guard)
 }
 
 #[cfg(test)]
@@ -950,7 +2004,11 @@ mod tests {
     fn test_logger_with_file_appender() {
         let file_appender = LoggerFileAppender {
             enable: true,
+            name: None,
+            filter: None,
             non_blocking: false,
+            buffered_lines_limit: None,
+            lossy: true,
             level: Some(Level::INFO),
             ansi: false,
             format: Some(LogFormat::Json),
@@ -959,6 +2017,11 @@ mod tests {
             filename_prefix: Some("test".to_string()),
             filename_suffix: Some("log".to_string()),
             max_log_files: 10,
+            max_bytes: None,
+            max_total_bytes: None,
+            max_age_secs: None,
+            compression: LogCompression::None,
+            json: None,
         };
 
         let logger = Logger::new("test-service")
@@ -1013,6 +2076,203 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_env_file_appender_rolling_parsing() {
+        #[cfg(feature = "env")]
+        {
+            let vars = [
+                ("LOG_FILE_ENABLE", "true"),
+                ("LOG_FILE_MAX_BYTES", "100MB"),
+                ("LOG_FILE_MAX_TOTAL_BYTES", "1073741824"),
+                ("LOG_FILE_MAX_AGE_SECS", "86400"),
+                ("LOG_FILE_COMPRESSION", "gzip"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+            let file_appender: LoggerFileAppender =
+                envy::prefixed("LOG_FILE_").from_iter(vars).unwrap();
+
+            assert_eq!(file_appender.max_bytes, Some(100 * 1024 * 1024));
+            assert_eq!(file_appender.max_total_bytes, Some(1024 * 1024 * 1024));
+            assert_eq!(file_appender.max_age_secs, Some(86400));
+            assert_eq!(file_appender.compression, LogCompression::Gzip);
+
+            let vars = [("LOG_FILE_ENABLE", "true"), ("LOG_FILE_MAX_BYTES", "lots")]
+                .map(|(key, value)| (key.to_string(), value.to_string()));
+            assert!(envy::prefixed("LOG_FILE_")
+                .from_iter::<_, LoggerFileAppender>(vars)
+                .is_err());
+
+            // Like `LoggerFileAppender::default()`, a configured appender is enabled
+            let vars = [("LOG_FILE_DIR", "/var/log/app")]
+                .map(|(key, value)| (key.to_string(), value.to_string()));
+            let file_appender: LoggerFileAppender =
+                envy::prefixed("LOG_FILE_").from_iter(vars).unwrap();
+            assert!(file_appender.enable);
+            assert_eq!(file_appender.enable, LoggerFileAppender::default().enable);
+        }
+    }
+
+    #[test]
+    fn test_env_validation_collects_all_issues() {
+        #[cfg(feature = "env")]
+        {
+            let vars = [
+                ("LOG_SAMPLE_RATIO", "5"),
+                ("LOG_METRICS_INTERVAL_SECS", "0"),
+                ("LOG_FORMAT", "xml"),
+                ("LOG_LEVL", "debug"),
+                ("LOG_FILE_ENABLE", "true"),
+                ("LOG_FILE_MAX_BYTES", "lots"),
+                ("LOG_FILE_0_LEVEL", "loud"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+            let err = logger_from_vars("LOG_", &vars)
+                .unwrap_err()
+                .downcast::<ValidationError>()
+                .unwrap();
+            let paths: Vec<_> = err.issues.iter().map(|issue| issue.path.as_str()).collect();
+            assert_eq!(
+                paths,
+                [
+                    "LOG_FORMAT",
+                    "LOG_FILE_MAX_BYTES",
+                    "LOG_FILE_0_LEVEL",
+                    "sample_ratio",
+                    "metrics_interval_secs",
+                ]
+            );
+            assert!(err.issues[0].message.contains("xml"));
+
+            let vars = [
+                ("LOG_LEVEL", "debug"),
+                ("LOG_LEVL", "debug"),
+                ("LOG_LOGFMT_SPAN_FIELD_PREFIX", "span_name"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+            let (logger, warnings) = logger_from_vars("LOG_", &vars).unwrap();
+            assert_eq!(logger.level, Level::DEBUG);
+            assert_eq!(logger.logfmt_span_field_prefix, SpanFieldPrefix::SpanName);
+            assert_eq!(warnings, ["Unknown environment variable LOG_LEVL"]);
+        }
+    }
+
+    #[test]
+    fn test_env_non_blocking_policy_parsing() {
+        #[cfg(feature = "env")]
+        {
+            let vars = [
+                ("LOG_FILE_ENABLE", "true"),
+                ("LOG_FILE_NON_BLOCKING", "true"),
+                ("LOG_FILE_BUFFERED_LINES_LIMIT", "4096"),
+                ("LOG_FILE_LOSSY", "false"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+            let file_appender: LoggerFileAppender =
+                envy::prefixed("LOG_FILE_").from_iter(vars).unwrap();
+            assert_eq!(file_appender.buffered_lines_limit, Some(4096));
+            assert!(!file_appender.lossy);
+
+            let vars = [("LOG_CONSOLE_BUFFERED_LINES_LIMIT", "512")]
+                .map(|(key, value)| (key.to_string(), value.to_string()));
+            let logger: Logger = envy::prefixed("LOG_").from_iter(vars).unwrap();
+            assert_eq!(logger.console_buffered_lines_limit, Some(512));
+            assert!(logger.console_lossy);
+        }
+    }
+
+    #[test]
+    fn test_env_multiple_file_appenders() {
+        #[cfg(feature = "env")]
+        {
+            let vars = [
+                ("LOG_FILE_0_ENABLE", "true"),
+                ("LOG_FILE_0_FILTER", "warn"),
+                ("LOG_FILE_0_FILENAME_PREFIX", "error"),
+                ("LOG_FILE_1_ENABLE", "true"),
+                ("LOG_FILE_1_JSON_SPAN_LIST", "false"),
+                ("LOG_FILE_NAMES", "audit"),
+                ("LOG_FILE_AUDIT_ENABLE", "true"),
+                ("LOG_FILE_AUDIT_FILTER", "audit=info"),
+                ("LOG_FILE_AUDIT_FORMAT", "json"),
+                ("LOG_FILE_AUDIT_DIR", "/var/log/audit"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+
+            let file_appenders = file_appenders_from_vars("LOG_FILE_", &vars).unwrap();
+            assert_eq!(file_appenders.len(), 3);
+
+            assert_eq!(file_appenders[0].filter.as_deref(), Some("warn"));
+            assert_eq!(file_appenders[0].filename_prefix_or_default(), "error");
+            assert!(file_appenders[1].filter.is_none());
+            assert!(!file_appenders[1].json.as_ref().unwrap().span_list);
+
+            let audit = &file_appenders[2];
+            assert_eq!(audit.name.as_deref(), Some("audit"));
+            assert_eq!(audit.filename_prefix_or_default(), "audit");
+            assert_eq!(audit.format, Some(LogFormat::Json));
+            assert_eq!(audit.dir_or_default(), "/var/log/audit");
+            assert!(audit.build_filter().unwrap().is_some());
+
+            // A listed name without variables is an error.
+            let vars = [("LOG_FILE_NAMES".to_string(), "missing".to_string())];
+            assert!(file_appenders_from_vars("LOG_FILE_", &vars).is_err());
+        }
+    }
+
+    #[test]
+    fn test_env_syslog_and_journald_parsing() {
+        #[cfg(feature = "env")]
+        {
+            use crate::logs::{SyslogFacility, SyslogTransport};
+
+            let vars = [
+                ("LOG_SYSLOG_ENABLE", "true"),
+                ("LOG_SYSLOG_TRANSPORT", "tcp"),
+                ("LOG_SYSLOG_ADDRESS", "10.0.0.1:601"),
+                ("LOG_SYSLOG_FACILITY", "local3"),
+                ("LOG_SYSLOG_FORMAT", "logfmt"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+            let syslog: SyslogConfig = sink_from_vars("LOG_SYSLOG_", &vars).unwrap().unwrap();
+            assert_eq!(syslog.transport, SyslogTransport::Tcp);
+            assert_eq!(syslog.address.as_deref(), Some("10.0.0.1:601"));
+            assert_eq!(syslog.facility, SyslogFacility::Local3);
+            assert_eq!(syslog.format, Some(LogFormat::Logfmt));
+
+            let journald: Option<JournaldConfig> = sink_from_vars("LOG_JOURNALD_", &vars).unwrap();
+            assert!(journald.is_none());
+
+            let vars = [
+                ("LOG_SYSLOG_ENABLE", "true"),
+                ("LOG_SYSLOG_TRANSPORT", "smtp"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()));
+            assert!(sink_from_vars::<SyslogConfig>("LOG_SYSLOG_", &vars).is_err());
+        }
+    }
+
+    #[test]
+    fn test_file_appender_filter() {
+        let file_appender = LoggerFileAppender::new("audit");
+        assert!(file_appender.build_filter().unwrap().is_none());
+        assert!(file_appender
+            .clone()
+            .with_filter("audit=info")
+            .build_filter()
+            .unwrap()
+            .is_some());
+        assert!(file_appender
+            .with_filter("audit=loud")
+            .build_filter()
+            .is_err());
+
+        // Without a level of its own, only the global filter applies
+        let merged = LoggerFileAppender::new("app")
+            .merge_with_logger(&Logger::new("test-service").with_level(Level::DEBUG));
+        assert_eq!(merged.level, None);
+        assert!(merged.build_filter().unwrap().is_none());
+    }
+
     #[test]
     fn test_simple_env_parsing() {
         #[cfg(feature = "env")]
@@ -1034,7 +2294,7 @@ mod tests {
                 assert!(fa.enable);
             } else {
                 println!("Failed to parse file appender");
-                assert!(false, "Should have parsed file appender");
+                panic!("Should have parsed file appender");
             }
         }
     }
@@ -1054,11 +2314,30 @@ mod tests {
         assert!(logger.console_enabled);
     }
 
+    #[test]
+    fn test_deserialize_console_target() {
+        let logger: Logger =
+            serde_json::from_str(r#"{"console_target": "split", "console_non_blocking": true}"#)
+                .unwrap();
+        assert_eq!(logger.console_target, ConsoleTarget::Split);
+        assert!(logger.console_non_blocking);
+
+        let logger: Logger = serde_json::from_str("{}").unwrap();
+        assert_eq!(logger.console_target, ConsoleTarget::Stdout);
+        assert!(!logger.console_non_blocking);
+
+        assert!(serde_json::from_str::<Logger>(r#"{"console_target": "tty"}"#).is_err());
+    }
+
     #[test]
     fn test_logger_console_and_file_combination() {
         let file_appender = LoggerFileAppender {
             enable: true,
+            name: None,
+            filter: None,
             non_blocking: false,
+            buffered_lines_limit: None,
+            lossy: true,
             level: Some(Level::INFO),
             ansi: false,
             format: Some(LogFormat::Json),
@@ -1067,6 +2346,11 @@ mod tests {
             filename_prefix: Some("test".to_string()),
             filename_suffix: Some("log".to_string()),
             max_log_files: 10,
+            max_bytes: None,
+            max_total_bytes: None,
+            max_age_secs: None,
+            compression: LogCompression::None,
+            json: None,
         };
 
         // Test both console and file enabled
@@ -1090,4 +2374,102 @@ mod tests {
         assert!(logger.console_enabled);
         assert!(logger.file_appender.is_none());
     }
+
+    #[test]
+    fn test_build_without_installing() {
+        let logger = Logger::new("test-service")
+            .with_sdk_disabled(true)
+            .with_level(Level::WARN);
+        let (subscriber, guard) = logger.build().unwrap();
+        assert!(guard.tracer_provider().is_none());
+        assert!(guard.meter_provider().is_none());
+
+        assert_eq!(
+            tracing::Subscriber::max_level_hint(&subscriber),
+            Some(tracing::level_filters::LevelFilter::WARN)
+        );
+    }
+
+    // The OTLP exporters connect lazily on the runtime, which must keep running while the
+    // guard shuts the providers down
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_provider_switches() {
+        for traces_enabled in [false, true] {
+            for metrics_enabled in [false, true] {
+                for sdk_disabled in [false, true] {
+                    let (_subscriber, guard) = Logger::new("test-service")
+                        .with_traces_enabled(traces_enabled)
+                        .with_metrics_enabled(metrics_enabled)
+                        .with_sdk_disabled(sdk_disabled)
+                        .build()
+                        .unwrap();
+                    let case = format!(
+                        "traces_enabled={traces_enabled} metrics_enabled={metrics_enabled} sdk_disabled={sdk_disabled}"
+                    );
+                    assert_eq!(
+                        guard.tracer_provider().is_some(),
+                        traces_enabled && !sdk_disabled,
+                        "{case}"
+                    );
+                    assert_eq!(
+                        guard.meter_provider().is_some(),
+                        metrics_enabled && !sdk_disabled,
+                        "{case}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_resource_attributes() {
+        let logger = Logger::new("test-service")
+            .with_attributes(vec![
+                KeyValue::new("service.version", "0.0.0"),
+                KeyValue::new("team", "payments"),
+            ])
+            .with_service_version("1.2.3")
+            .with_service_namespace("shop")
+            .with_deployment_environment("staging")
+            .with_service_instance_id("instance-1");
+        assert_eq!(
+            logger.resource_attributes(),
+            [
+                KeyValue::new("team", "payments"),
+                KeyValue::new("service.version", "1.2.3"),
+                KeyValue::new("service.namespace", "shop"),
+                KeyValue::new("service.instance.id", "instance-1"),
+                KeyValue::new("deployment.environment.name", "staging"),
+                KeyValue::new("deployment.environment", "staging"),
+            ]
+        );
+
+        assert!(Logger::new("test-service").resource_attributes().is_empty());
+
+        // a random instance ID, generated once, unless set in OTEL_RESOURCE_ATTRIBUTES
+        let instance_id = default_service_instance_id(&Resource::builder_empty().build()).unwrap();
+        assert_eq!(instance_id.key.as_str(), "service.instance.id");
+        assert_eq!(instance_id.value.as_str().len(), 36);
+        assert_eq!(
+            default_service_instance_id(&Resource::builder_empty().build()),
+            Some(instance_id)
+        );
+        let env = Resource::builder_empty()
+            .with_attribute(KeyValue::new("service.instance.id", "pod-1"))
+            .build();
+        assert_eq!(default_service_instance_id(&env), None);
+        let logger = Logger::new("test-service")
+            .with_attributes(vec![KeyValue::new("service.instance.id", "pod-1")]);
+        assert_eq!(
+            logger.resource_attributes(),
+            [KeyValue::new("service.instance.id", "pod-1")]
+        );
+
+        let logger = crate::logger!();
+        assert_eq!(logger.service_name, env!("CARGO_PKG_NAME"));
+        assert_eq!(
+            logger.service_version.as_deref(),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+    }
 }
@@ -1,11 +1,43 @@
+pub mod attributes;
+#[cfg(feature = "config")]
+pub mod config_file;
+pub mod format;
+pub mod guard;
+pub mod journald;
+pub mod json;
 pub mod layer;
+pub mod logfmt;
 pub mod logger;
+#[cfg(any(feature = "env", feature = "config"))]
+pub mod otel_env;
+pub mod rolling;
+pub mod schema;
+#[cfg(feature = "shutdown")]
+pub mod shutdown;
 pub mod subscriber;
+pub mod syslog;
+#[cfg(test)]
+mod testing;
+pub mod validate;
 
 // Re-exports
+pub use attributes::*;
+#[cfg(feature = "config")]
+pub use config_file::*;
+pub use format::*;
+pub use guard::*;
+pub use journald::*;
+pub use json::*;
 pub use layer::*;
+pub use logfmt::*;
 pub use logger::*;
+pub use rolling::*;
+pub use schema::*;
+#[cfg(feature = "shutdown")]
+pub use shutdown::*;
 pub use subscriber::*;
+pub use syslog::*;
+pub use validate::*;
 
 // Re-export FmtSpan
 pub use tracing_subscriber::fmt::format::FmtSpan;
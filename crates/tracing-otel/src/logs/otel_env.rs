@@ -0,0 +1,372 @@
+//! The standard `OTEL_*` environment variables.
+//!
+//! | Variable | Logger field |
+//! |----------|--------------|
+//! | `OTEL_SERVICE_NAME` | `service_name` |
+//! | `OTEL_RESOURCE_ATTRIBUTES` | `attributes`, and `service_name` from `service.name` |
+//! | `OTEL_TRACES_SAMPLER`, `OTEL_TRACES_SAMPLER_ARG` | `sample_ratio` and `parent_based_sampling` |
+//! | `OTEL_METRIC_EXPORT_INTERVAL` | `metrics_interval_secs`, rounded up to whole seconds |
+//! | `OTEL_TRACES_EXPORTER` | `traces_enabled`, `none` disables and `otlp` enables export |
+//! | `OTEL_METRICS_EXPORTER` | `metrics_enabled`, `none` disables and `otlp` enables export |
+//! | `OTEL_SDK_DISABLED` | `sdk_disabled` |
+//!
+//! A `LOG_*` variable takes precedence over the `OTEL_*` one for the same field, and both
+//! take precedence over a configuration file and the defaults. The service name is taken from
+//! `LOG_SERVICE_NAME`, then `OTEL_SERVICE_NAME`, then `service.name` in
+//! `OTEL_RESOURCE_ATTRIBUTES`. Resource attributes are added for the keys not already set by
+//! `LOG_ATTRIBUTES` or the configuration file.
+//!
+//! As in the OpenTelemetry SDKs, an invalid `OTEL_*` value is ignored with a warning.
+//! Logs are only written to the console, file, syslog and journald outputs, so
+//! `OTEL_LOGS_EXPORTER` is not read.
+
+use crate::logs::{attributes::percent_decode, Logger};
+use opentelemetry::KeyValue;
+
+/// Apply the `OTEL_*` variables for which the matching `{prefix}*` variable is not set,
+/// adding a warning for each invalid one.
+pub(crate) fn apply_otel_vars(
+    logger: &mut Logger,
+    prefix: &str,
+    vars: &[(String, String)],
+    warnings: &mut Vec<String>,
+) {
+    let var = |name: &str| {
+        vars.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let overridden = |field: &str| var(&format!("{prefix}{field}")).is_some();
+
+    let mut resource_service_name = None;
+    if let Some(value) = var("OTEL_RESOURCE_ATTRIBUTES") {
+        match parse_resource_attributes(value) {
+            Some(attributes) => {
+                for (key, value) in attributes {
+                    if key == "service.name" {
+                        resource_service_name = Some(value);
+                    } else if !logger
+                        .attributes
+                        .iter()
+                        .any(|attribute| attribute.key.as_str() == key)
+                    {
+                        logger.attributes.push(KeyValue::new(key, value));
+                    }
+                }
+            }
+            None => warnings.push(format!(
+                "Ignoring OTEL_RESOURCE_ATTRIBUTES: expected percent-encoded `key=value` pairs, got '{value}'"
+            )),
+        }
+    }
+
+    if !overridden("SERVICE_NAME") {
+        if let Some(service_name) = var("OTEL_SERVICE_NAME")
+            .map(str::to_string)
+            .or(resource_service_name)
+        {
+            logger.service_name = service_name;
+        }
+    }
+
+    if let Some(sampler) = var("OTEL_TRACES_SAMPLER").filter(|_| !overridden("SAMPLE_RATIO")) {
+        if let Some((ratio, parent_based)) =
+            sampler_settings(sampler, var("OTEL_TRACES_SAMPLER_ARG"), warnings)
+        {
+            logger.sample_ratio = ratio;
+            if !overridden("PARENT_BASED_SAMPLING") {
+                logger.parent_based_sampling = parent_based;
+            }
+        }
+    }
+
+    if let Some(interval) =
+        var("OTEL_METRIC_EXPORT_INTERVAL").filter(|_| !overridden("METRICS_INTERVAL_SECS"))
+    {
+        match interval.parse::<u64>() {
+            Ok(millis) if millis > 0 => logger.metrics_interval_secs = millis.div_ceil(1000),
+            _ => warnings.push(format!(
+                "Ignoring OTEL_METRIC_EXPORT_INTERVAL: expected a positive number of milliseconds, got '{interval}'"
+            )),
+        }
+    }
+
+    if let Some(exporter) = var("OTEL_TRACES_EXPORTER").filter(|_| !overridden("TRACES_ENABLED")) {
+        if let Some(enabled) = exporter_enabled("OTEL_TRACES_EXPORTER", exporter, warnings) {
+            logger.traces_enabled = enabled;
+        }
+    }
+
+    if let Some(exporter) = var("OTEL_METRICS_EXPORTER").filter(|_| !overridden("METRICS_ENABLED"))
+    {
+        if let Some(enabled) = exporter_enabled("OTEL_METRICS_EXPORTER", exporter, warnings) {
+            logger.metrics_enabled = enabled;
+        }
+    }
+
+    if let Some(disabled) = var("OTEL_SDK_DISABLED").filter(|_| !overridden("SDK_DISABLED")) {
+        match disabled.to_ascii_lowercase().as_str() {
+            "true" => logger.sdk_disabled = true,
+            "false" => logger.sdk_disabled = false,
+            _ => warnings.push(format!(
+                "Ignoring OTEL_SDK_DISABLED: expected `true` or `false`, got '{disabled}'"
+            )),
+        }
+    }
+}
+
+// The sample ratio of a sampler and whether it is parent-based
+fn sampler_settings(
+    sampler: &str,
+    arg: Option<&str>,
+    warnings: &mut Vec<String>,
+) -> Option<(f64, bool)> {
+    let sampler = sampler.to_ascii_lowercase();
+    let (name, parent_based) = match sampler.strip_prefix("parentbased_") {
+        Some(name) => (name, true),
+        None => (sampler.as_str(), false),
+    };
+    let ratio = match name {
+        "always_on" => 1.0,
+        "always_off" => 0.0,
+        "traceidratio" => match arg.map(str::parse::<f64>) {
+            None => 1.0,
+            Some(Ok(ratio)) if (0.0..=1.0).contains(&ratio) => ratio,
+            // Sampling every trace because of a typo could overload the backend
+            Some(_) => {
+                warnings.push(format!(
+                    "Ignoring OTEL_TRACES_SAMPLER and OTEL_TRACES_SAMPLER_ARG: expected a ratio between 0.0 and 1.0, got '{}'",
+                    arg.unwrap_or_default()
+                ));
+                return None;
+            }
+        },
+        _ => {
+            warnings.push(format!(
+                "Ignoring OTEL_TRACES_SAMPLER: unsupported sampler '{sampler}'"
+            ));
+            return None;
+        }
+    };
+    Some((ratio, parent_based))
+}
+
+// Whether an exporter list enables OTLP export, the only exporter supported here
+fn exporter_enabled(name: &str, exporters: &str, warnings: &mut Vec<String>) -> Option<bool> {
+    let exporters: Vec<String> = exporters
+        .split(',')
+        .map(|exporter| exporter.trim().to_ascii_lowercase())
+        .filter(|exporter| !exporter.is_empty())
+        .collect();
+    if exporters == ["none"] {
+        return Some(false);
+    }
+    let unsupported: Vec<&str> = exporters
+        .iter()
+        .map(String::as_str)
+        .filter(|exporter| *exporter != "otlp")
+        .collect();
+    if !unsupported.is_empty() {
+        warnings.push(format!(
+            "Ignoring {name}: unsupported exporter '{}', expected `otlp` or `none`",
+            unsupported.join(",")
+        ));
+    }
+    exporters
+        .iter()
+        .any(|exporter| exporter == "otlp")
+        .then_some(true)
+}
+
+/// Parse `key=value,key2=value2` with percent-encoded keys and values, as used by
+/// `OTEL_RESOURCE_ATTRIBUTES`. Returns `None` if any pair is invalid.
+pub(crate) fn parse_resource_attributes(s: &str) -> Option<Vec<(String, String)>> {
+    s.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            let key = percent_decode(key.trim())?;
+            let value = percent_decode(value.trim())?;
+            (!key.is_empty()).then_some((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_otel_vars() {
+        let mut logger = Logger::default();
+        let mut warnings = Vec::new();
+        let vars = env(&[
+            ("OTEL_SERVICE_NAME", "checkout"),
+            (
+                "OTEL_RESOURCE_ATTRIBUTES",
+                "service.name=ignored,deployment.environment=prod,team=a%2Cb%3Dc",
+            ),
+            ("OTEL_TRACES_SAMPLER", "parentbased_traceidratio"),
+            ("OTEL_TRACES_SAMPLER_ARG", "0.25"),
+            ("OTEL_METRIC_EXPORT_INTERVAL", "1500"),
+            ("OTEL_SDK_DISABLED", "TRUE"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert_eq!(logger.service_name, "checkout");
+        assert_eq!(
+            logger.attributes,
+            [
+                KeyValue::new("deployment.environment", "prod"),
+                KeyValue::new("team", "a,b=c"),
+            ]
+        );
+        assert_eq!(logger.sample_ratio, 0.25);
+        assert!(logger.parent_based_sampling);
+        assert_eq!(logger.metrics_interval_secs, 2);
+        assert!(logger.sdk_disabled);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_sampler_vars() {
+        let mut logger = Logger::default();
+        let mut warnings = Vec::new();
+        let vars = env(&[
+            ("OTEL_TRACES_SAMPLER", "traceidratio"),
+            ("OTEL_TRACES_SAMPLER_ARG", "0.1"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert_eq!(logger.sample_ratio, 0.1);
+        assert!(!logger.parent_based_sampling);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        // LOG_PARENT_BASED_SAMPLING takes precedence
+        let mut logger = Logger::default();
+        let vars = env(&[
+            ("OTEL_TRACES_SAMPLER", "always_off"),
+            ("LOG_PARENT_BASED_SAMPLING", "true"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert_eq!(logger.sample_ratio, 0.0);
+        assert!(logger.parent_based_sampling);
+    }
+
+    #[test]
+    fn test_log_vars_take_precedence() {
+        let mut logger = Logger::new("from-log").with_attributes(vec![KeyValue::new("team", "x")]);
+        let mut warnings = Vec::new();
+        let vars = env(&[
+            ("LOG_SERVICE_NAME", "from-log"),
+            ("OTEL_SERVICE_NAME", "from-otel"),
+            ("OTEL_RESOURCE_ATTRIBUTES", "team=y,region=eu"),
+            ("LOG_SAMPLE_RATIO", "1.0"),
+            ("OTEL_TRACES_SAMPLER", "always_off"),
+            ("LOG_SDK_DISABLED", "false"),
+            ("OTEL_SDK_DISABLED", "true"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert_eq!(logger.service_name, "from-log");
+        assert_eq!(
+            logger.attributes,
+            [KeyValue::new("team", "x"), KeyValue::new("region", "eu")]
+        );
+        assert_eq!(logger.sample_ratio, 1.0);
+        assert!(!logger.sdk_disabled);
+
+        // `service.name` in the resource attributes is used without OTEL_SERVICE_NAME
+        let mut logger = Logger::default();
+        let mut warnings = Vec::new();
+        apply_otel_vars(
+            &mut logger,
+            "LOG_",
+            &env(&[("OTEL_RESOURCE_ATTRIBUTES", "service.name=billing")]),
+            &mut warnings,
+        );
+        assert_eq!(logger.service_name, "billing");
+    }
+
+    #[test]
+    fn test_invalid_otel_vars_are_ignored() {
+        let mut logger = Logger::default().with_sample_ratio(0.5);
+        let mut warnings = Vec::new();
+        let vars = env(&[
+            ("OTEL_RESOURCE_ATTRIBUTES", "team=%zz"),
+            ("OTEL_TRACES_SAMPLER", "traceidratio"),
+            ("OTEL_TRACES_SAMPLER_ARG", "5"),
+            ("OTEL_METRIC_EXPORT_INTERVAL", "0"),
+            ("OTEL_SDK_DISABLED", "yes"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert!(logger.attributes.is_empty());
+        assert_eq!(logger.sample_ratio, 0.5);
+        assert!(logger.parent_based_sampling);
+        assert_eq!(logger.metrics_interval_secs, 30);
+        assert!(!logger.sdk_disabled);
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+
+        let mut logger = Logger::default();
+        let mut warnings = Vec::new();
+        apply_otel_vars(
+            &mut logger,
+            "LOG_",
+            &env(&[("OTEL_TRACES_SAMPLER", "jaeger_remote")]),
+            &mut warnings,
+        );
+        assert_eq!(logger.sample_ratio, 1.0);
+        assert_eq!(
+            warnings,
+            ["Ignoring OTEL_TRACES_SAMPLER: unsupported sampler 'jaeger_remote'"]
+        );
+    }
+
+    #[test]
+    fn test_exporter_vars() {
+        let mut logger = Logger::default();
+        let mut warnings = Vec::new();
+        let vars = env(&[
+            ("OTEL_TRACES_EXPORTER", "none"),
+            ("OTEL_METRICS_EXPORTER", "otlp"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert!(!logger.traces_enabled);
+        assert!(logger.metrics_enabled);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        // LOG_TRACES_ENABLED takes precedence
+        let mut logger = Logger::default();
+        let mut warnings = Vec::new();
+        let vars = env(&[
+            ("LOG_TRACES_ENABLED", "true"),
+            ("OTEL_TRACES_EXPORTER", "none"),
+            ("OTEL_METRICS_EXPORTER", "None"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert!(logger.traces_enabled);
+        assert!(!logger.metrics_enabled);
+
+        // OTLP is kept alongside an unsupported exporter, which is reported
+        let mut logger = Logger::default().with_traces_enabled(false);
+        let mut warnings = Vec::new();
+        let vars = env(&[
+            ("OTEL_TRACES_EXPORTER", "otlp,zipkin"),
+            ("OTEL_METRICS_EXPORTER", "prometheus"),
+        ]);
+        apply_otel_vars(&mut logger, "LOG_", &vars, &mut warnings);
+        assert!(logger.traces_enabled);
+        assert!(logger.metrics_enabled);
+        assert_eq!(
+            warnings,
+            [
+                "Ignoring OTEL_TRACES_EXPORTER: unsupported exporter 'zipkin', expected `otlp` or `none`",
+                "Ignoring OTEL_METRICS_EXPORTER: unsupported exporter 'prometheus', expected `otlp` or `none`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,961 @@
+use crate::logs::layer::LogRollingRotation;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::Cell,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// The compression applied to rotated log files.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum LogCompression {
+    /// Keep rotated files as they are.
+    #[serde(rename = "none")]
+    #[default]
+    None,
+    /// Compress rotated files to `.gz`.
+    #[serde(rename = "gzip")]
+    Gzip,
+    /// Compress rotated files to `.zst`. Requires the `zstd` feature.
+    #[serde(rename = "zstd")]
+    Zstd,
+}
+
+impl LogCompression {
+    /// The extension appended to compressed files.
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Gzip => Some("gz"),
+            Self::Zstd => Some("zst"),
+        }
+    }
+}
+
+/// Builder for a [`RollingFileWriter`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tracing_otel_extra::logs::{LogCompression, LogRollingRotation, RollingFileWriter};
+///
+/// let writer = RollingFileWriter::builder()
+///     .rotation(LogRollingRotation::Daily)
+///     .filename_prefix("app")
+///     .filename_suffix("log")
+///     .max_bytes(100 * 1024 * 1024)
+///     .max_total_bytes(1024 * 1024 * 1024)
+///     .compression(LogCompression::Gzip)
+///     .build("./logs")
+///     .expect("failed to build the rolling writer");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RollingFileWriterBuilder {
+    rotation: LogRollingRotation,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    max_bytes: Option<u64>,
+    max_log_files: Option<usize>,
+    max_total_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    compression: LogCompression,
+}
+
+impl RollingFileWriterBuilder {
+    /// Set the time-based rotation. Defaults to hourly.
+    pub fn rotation(mut self, rotation: LogRollingRotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Set the filename prefix.
+    pub fn filename_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the filename suffix.
+    pub fn filename_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Rotate the current file once it would grow beyond `max_bytes`.
+    ///
+    /// Combined with a time-based rotation, a file is rotated on whichever comes first.
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Keep at most `max_log_files` files, including the current one.
+    pub fn max_log_files(mut self, max_log_files: usize) -> Self {
+        self.max_log_files = Some(max_log_files);
+        self
+    }
+
+    /// Delete the oldest rotated files once all files together exceed `max_total_bytes`.
+    pub fn max_total_bytes(mut self, max_total_bytes: u64) -> Self {
+        self.max_total_bytes = Some(max_total_bytes);
+        self
+    }
+
+    /// Delete rotated files last modified more than `max_age` ago.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Set the compression applied to rotated files.
+    pub fn compression(mut self, compression: LogCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Build the writer, creating `dir` if needed.
+    pub fn build(self, dir: impl AsRef<Path>) -> Result<RollingFileWriter> {
+        RollingFileWriter::new(self, dir.as_ref(), now_secs)
+    }
+}
+
+/// A rolling file writer with time and size based rotation, compression and retention.
+///
+/// The current file is named `{prefix}.{date}.{suffix}`, where the date is formatted according
+/// to the rotation in UTC, as with `tracing_appender::rolling`. When the current file reaches
+/// the maximum size, it is renamed to `{prefix}.{date}.{index}.{suffix}` and a new file is
+/// started. Rotated files are compressed and pruned on a background thread, so rotation never
+/// blocks on the disk for longer than a rename. Retention only considers files named exactly
+/// like this, so writers with different prefixes can share a directory.
+///
+/// Compression and retention failures are reported as ERROR events with the
+/// `tracing_otel_extra::logs::rolling` target, through the global default subscriber.
+pub struct RollingFileWriter {
+    config: RollingConfig,
+    state: Mutex<State>,
+    sender: Option<mpsc::Sender<Job>>,
+    worker: Option<JoinHandle<()>>,
+    clock: fn() -> u64,
+}
+
+impl fmt::Debug for RollingFileWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RollingFileWriter")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RollingConfig {
+    dir: PathBuf,
+    rotation: LogRollingRotation,
+    prefix: String,
+    suffix: String,
+    max_bytes: Option<u64>,
+    retention: Retention,
+}
+
+#[derive(Debug, Clone)]
+struct Retention {
+    max_log_files: Option<usize>,
+    max_total_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    compression: LogCompression,
+}
+
+#[derive(Debug)]
+struct State {
+    file: File,
+    path: PathBuf,
+    period: Option<u64>,
+    size: u64,
+}
+
+/// Work done on the background thread.
+enum Job {
+    /// Compress a rotated file, then prune old files.
+    Rotated { rotated: PathBuf, current: PathBuf },
+    /// Prune old files.
+    Prune { current: PathBuf },
+}
+
+impl RollingFileWriter {
+    /// Create a builder for a rolling file writer.
+    pub fn builder() -> RollingFileWriterBuilder {
+        RollingFileWriterBuilder::default()
+    }
+
+    fn new(builder: RollingFileWriterBuilder, dir: &Path, clock: fn() -> u64) -> Result<Self> {
+        let prefix = builder.prefix.unwrap_or_default();
+        let suffix = builder.suffix.unwrap_or_default();
+        if prefix.is_empty() && suffix.is_empty() && builder.rotation == LogRollingRotation::Never {
+            bail!("A filename prefix or suffix is required when the rotation is `never`");
+        }
+        if builder.max_bytes == Some(0) {
+            bail!("The maximum file size must be greater than zero");
+        }
+        if builder.compression == LogCompression::Zstd && !cfg!(feature = "zstd") {
+            bail!("zstd compression requires the `zstd` feature");
+        }
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+
+        let config = RollingConfig {
+            dir: dir.to_path_buf(),
+            rotation: builder.rotation,
+            prefix,
+            suffix,
+            max_bytes: builder.max_bytes,
+            retention: Retention {
+                max_log_files: builder.max_log_files,
+                max_total_bytes: builder.max_total_bytes,
+                max_age: builder.max_age,
+                compression: builder.compression,
+            },
+        };
+
+        let period = config.period_start(clock());
+        let path = config.current_path(period);
+        let (file, size) = open_append(&path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("rolling-file-writer".to_string())
+            .spawn({
+                let config = config.clone();
+                move || config.run_worker(receiver)
+            })
+            .context("Failed to spawn the rolling file writer thread")?;
+        let _ = sender.send(Job::Prune {
+            current: path.clone(),
+        });
+
+        Ok(Self {
+            config,
+            state: Mutex::new(State {
+                file,
+                path,
+                period,
+                size,
+            }),
+            sender: Some(sender),
+            worker: Some(worker),
+            clock,
+        })
+    }
+
+    /// The path of the file currently written to.
+    pub fn current_path(&self) -> PathBuf {
+        self.lock().path.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn roll_if_needed(&self, state: &mut State, incoming: usize) -> io::Result<()> {
+        let period = self.config.period_start((self.clock)());
+        if period != state.period {
+            let rotated = state.path.clone();
+            state.file.flush()?;
+            state.path = self.config.current_path(period);
+            state.period = period;
+            (state.file, state.size) = open_append(&state.path)?;
+            self.submit(rotated, state.path.clone());
+        } else if self
+            .config
+            .max_bytes
+            .is_some_and(|max| state.size > 0 && state.size + incoming as u64 > max)
+        {
+            state.file.flush()?;
+            let rotated = self.config.next_rotated_path(period)?;
+            fs::rename(&state.path, &rotated)?;
+            (state.file, state.size) = open_append(&state.path)?;
+            self.submit(rotated, state.path.clone());
+        }
+        Ok(())
+    }
+
+    fn submit(&self, rotated: PathBuf, current: PathBuf) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(Job::Rotated { rotated, current });
+        }
+    }
+}
+
+impl Drop for RollingFileWriter {
+    fn drop(&mut self) {
+        // Close the channel and wait for pending compressions.
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl io::Write for &RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.lock();
+        self.roll_if_needed(&mut state, buf.len())?;
+        let written = state.file.write(buf)?;
+        state.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().file.flush()
+    }
+}
+
+impl io::Write for RollingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RollingFileWriter {
+    type Writer = &'a RollingFileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self
+    }
+}
+
+impl RollingConfig {
+    fn period_secs(&self) -> Option<u64> {
+        match self.rotation {
+            LogRollingRotation::Minutely => Some(60),
+            LogRollingRotation::Hourly => Some(60 * 60),
+            LogRollingRotation::Daily => Some(24 * 60 * 60),
+            LogRollingRotation::Never => None,
+        }
+    }
+
+    fn period_start(&self, now: u64) -> Option<u64> {
+        self.period_secs().map(|secs| now - now % secs)
+    }
+
+    fn date(&self, period: Option<u64>) -> Option<String> {
+        let period = period?;
+        let (year, month, day) = civil_from_days((period / 86400) as i64);
+        let (hour, minute) = (period % 86400 / 3600, period % 3600 / 60);
+        Some(match self.rotation {
+            LogRollingRotation::Minutely => {
+                format!("{year:04}-{month:02}-{day:02}-{hour:02}-{minute:02}")
+            }
+            LogRollingRotation::Hourly => format!("{year:04}-{month:02}-{day:02}-{hour:02}"),
+            _ => format!("{year:04}-{month:02}-{day:02}"),
+        })
+    }
+
+    fn file_name(&self, period: Option<u64>, index: Option<u32>) -> String {
+        let index = index.map(|index| index.to_string());
+        [
+            Some(self.prefix.clone()),
+            self.date(period),
+            index,
+            Some(self.suffix.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+    }
+
+    fn current_path(&self, period: Option<u64>) -> PathBuf {
+        self.dir.join(self.file_name(period, None))
+    }
+
+    /// The path for the next size-rotated file of the period, after any existing one.
+    fn next_rotated_path(&self, period: Option<u64>) -> io::Result<PathBuf> {
+        let date = self.date(period);
+        let mut next = 1;
+        for entry in fs::read_dir(&self.dir)? {
+            let name = entry?.file_name();
+            let index = name
+                .to_str()
+                .and_then(|name| self.parse_file_name(name))
+                .and_then(|(_, file_date, index)| {
+                    (file_date == date.as_deref()).then_some(index)?
+                });
+            if let Some(index) = index {
+                next = next.max(index + 1);
+            }
+        }
+        Ok(self.dir.join(self.file_name(period, Some(next))))
+    }
+
+    /// The name of a file of this writer without its compression extension, if it is one.
+    fn uncompressed_name<'a>(&self, name: &'a str) -> Option<&'a str> {
+        self.parse_file_name(name).map(|(name, ..)| name)
+    }
+
+    /// Split a file name of the form `{prefix}[.{date}][.{index}].{suffix}[.gz|.zst]` into
+    /// the name without its compression extension, the date and the index.
+    ///
+    /// Names with anything else between the prefix and the suffix, such as the files of another
+    /// writer whose prefix starts with this one, are not files of this writer.
+    fn parse_file_name<'a>(
+        &self,
+        name: &'a str,
+    ) -> Option<(&'a str, Option<&'a str>, Option<u32>)> {
+        let name = ["gz", "zst"]
+            .iter()
+            .find_map(|ext| name.strip_suffix(ext)?.strip_suffix('.'))
+            .unwrap_or(name);
+        let mut rest = name;
+        if !self.prefix.is_empty() {
+            rest = rest.strip_prefix(self.prefix.as_str())?;
+            if !rest.is_empty() {
+                rest = rest.strip_prefix('.')?;
+            }
+        }
+        if !self.suffix.is_empty() {
+            rest = rest.strip_suffix(self.suffix.as_str())?;
+            if !rest.is_empty() {
+                rest = rest.strip_suffix('.')?;
+            }
+        }
+
+        let mut parts = rest.split('.').filter(|_| !rest.is_empty());
+        let date = match self.period_secs() {
+            Some(_) => Some(parts.next().filter(|date| self.is_date(date))?),
+            None => None,
+        };
+        let index = match parts.next() {
+            Some(index) if index.bytes().all(|b| b.is_ascii_digit()) => Some(index.parse().ok()?),
+            Some(_) => return None,
+            None => None,
+        };
+        if parts.next().is_some() || name.is_empty() {
+            return None;
+        }
+        Some((name, date, index))
+    }
+
+    /// Whether `date` has the format of the dates of the rotation.
+    fn is_date(&self, date: &str) -> bool {
+        let pattern = match self.rotation {
+            LogRollingRotation::Minutely => "0000-00-00-00-00",
+            LogRollingRotation::Hourly => "0000-00-00-00",
+            _ => "0000-00-00",
+        };
+        date.len() == pattern.len()
+            && date.bytes().zip(pattern.bytes()).all(|(c, p)| match p {
+                b'0' => c.is_ascii_digit(),
+                _ => c == p,
+            })
+    }
+
+    fn run_worker(&self, receiver: mpsc::Receiver<Job>) {
+        for job in receiver {
+            let current = match job {
+                Job::Rotated { rotated, current } => {
+                    if let Err(error) = compress(&rotated, self.retention.compression) {
+                        if error.kind() != io::ErrorKind::NotFound {
+                            report(|| {
+                                tracing::error!(
+                                    path = %rotated.display(),
+                                    %error,
+                                    "Failed to compress log file"
+                                )
+                            });
+                        }
+                    }
+                    current
+                }
+                Job::Prune { current } => current,
+            };
+            if let Err(error) = self.prune(&current) {
+                report(|| {
+                    tracing::error!(
+                        dir = %self.dir.display(),
+                        %error,
+                        "Failed to prune log files"
+                    )
+                });
+            }
+        }
+    }
+
+    /// Delete rotated files beyond the count, total size or age limits, oldest first.
+    fn prune(&self, current: &Path) -> io::Result<()> {
+        let Retention {
+            max_log_files,
+            max_total_bytes,
+            max_age,
+            ..
+        } = self.retention;
+        if max_log_files.is_none() && max_total_bytes.is_none() && max_age.is_none() {
+            return Ok(());
+        }
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = entry.metadata()?;
+            let file_name = entry.file_name();
+            let Some(name) = file_name.to_str().and_then(|n| self.uncompressed_name(n)) else {
+                continue;
+            };
+            if metadata.is_file() && path != current {
+                // Files rotated within the timestamp resolution are ordered by their index.
+                let key = (metadata.modified()?, name.len(), name.to_string());
+                files.push((path, key, metadata.len()));
+            }
+        }
+        files.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let now = SystemTime::now();
+        let mut kept = 1;
+        let mut total = fs::metadata(current).map(|m| m.len()).unwrap_or_default();
+        for (path, (modified, ..), len) in files {
+            let expired = max_age
+                .is_some_and(|max_age| now.duration_since(modified).unwrap_or_default() > max_age);
+            let too_many = max_log_files.is_some_and(|max| kept >= max);
+            let too_large = max_total_bytes.is_some_and(|max| total + len > max);
+            if expired || too_many || too_large {
+                fs::remove_file(&path)?;
+            } else {
+                kept += 1;
+                total += len;
+            }
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    // Set while a failure is reported, so that the outputs of the event cannot report
+    // another one from the same thread
+    static REPORTING: Cell<bool> = const { Cell::new(false) };
+}
+
+// Report a failure of the background thread, unless one is already being reported
+fn report(event: impl FnOnce()) {
+    if !REPORTING.replace(true) {
+        event();
+        REPORTING.set(false);
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+/// Compress `path` to `{path}.{ext}` and remove it, keeping its modification time.
+fn compress(path: &Path, compression: LogCompression) -> io::Result<()> {
+    let Some(extension) = compression.extension() else {
+        return Ok(());
+    };
+    let mut target = path.as_os_str().to_owned();
+    target.push(".");
+    target.push(extension);
+
+    let mut input = File::open(path)?;
+    let modified = input.metadata()?.modified()?;
+    let output = File::create(&target)?;
+    let output = match compression {
+        LogCompression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+            io::copy(&mut input, &mut encoder)?;
+            encoder.finish()?
+        }
+        _ => compress_zstd(&mut input, output)?,
+    };
+    output.set_modified(modified)?;
+    drop(input);
+    fs::remove_file(path)
+}
+
+#[cfg(feature = "zstd")]
+fn compress_zstd(input: &mut File, output: File) -> io::Result<File> {
+    let mut encoder = zstd::Encoder::new(output, 0)?;
+    io::copy(input, &mut encoder)?;
+    encoder.finish()
+}
+
+#[cfg(not(feature = "zstd"))]
+fn compress_zstd(_input: &mut File, _output: File) -> io::Result<File> {
+    Err(io::Error::other(
+        "zstd compression requires the `zstd` feature",
+    ))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Convert days since the Unix epoch to a `(year, month, day)` date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// Parse a byte size from a number or a string like `100MB`, using 1024-based units
+pub(crate) fn deserialize_byte_size_optional<'de, D>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct ByteSizeVisitor;
+
+    impl serde::de::Visitor<'_> for ByteSizeVisitor {
+        type Value = Option<u64>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a size in bytes, e.g. `1048576` or `100MB`")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Some(v))
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            u64::try_from(v)
+                .map(Some)
+                .map_err(|_| E::custom(format!("Invalid size: '{v}'")))
+        }
+
+        fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            if s.trim().is_empty() {
+                return Ok(None);
+            }
+            parse_byte_size(s)
+                .map(Some)
+                .ok_or_else(|| E::custom(format!("Invalid size: '{s}'")))
+        }
+
+        fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(ByteSizeVisitor)
+}
+
+fn parse_byte_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::Read,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tracing-otel-rolling-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("1024"), Some(1024));
+        assert_eq!(parse_byte_size("10K"), Some(10 * 1024));
+        assert_eq!(parse_byte_size("100MB"), Some(100 * 1024 * 1024));
+        assert_eq!(parse_byte_size("1 GiB"), Some(1024 * 1024 * 1024));
+        assert_eq!(parse_byte_size("10 parsecs"), None);
+        assert_eq!(parse_byte_size("MB"), None);
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_time_and_size_rotation() {
+        static NOW: AtomicU64 = AtomicU64::new(1_704_067_200); // 2024-01-01T00:00:00Z
+        fn clock() -> u64 {
+            NOW.load(Ordering::SeqCst)
+        }
+
+        let dir = temp_dir("hybrid");
+        let builder = RollingFileWriter::builder()
+            .rotation(LogRollingRotation::Hourly)
+            .filename_prefix("app")
+            .filename_suffix("log")
+            .max_bytes(10);
+        let mut writer = RollingFileWriter::new(builder, &dir, clock).unwrap();
+
+        writer.write_all(b"first\n").unwrap();
+        writer.write_all(b"second\n").unwrap();
+        writer.write_all(b"third\n").unwrap();
+        NOW.fetch_add(3600, Ordering::SeqCst);
+        writer.write_all(b"next hour\n").unwrap();
+        drop(writer);
+
+        assert_eq!(
+            file_names(&dir),
+            [
+                "app.2024-01-01-00.1.log",
+                "app.2024-01-01-00.2.log",
+                "app.2024-01-01-00.log",
+                "app.2024-01-01-01.log",
+            ]
+        );
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("app.2024-01-01-00.1.log"), "first\n");
+        assert_eq!(read("app.2024-01-01-00.2.log"), "second\n");
+        assert_eq!(read("app.2024-01-01-00.log"), "third\n");
+        assert_eq!(read("app.2024-01-01-01.log"), "next hour\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_gzip_compression_and_retention() {
+        let dir = temp_dir("gzip");
+        let mut writer = RollingFileWriter::builder()
+            .rotation(LogRollingRotation::Never)
+            .filename_prefix("app")
+            .filename_suffix("log")
+            .max_bytes(8)
+            .max_log_files(3)
+            .compression(LogCompression::Gzip)
+            .build(&dir)
+            .unwrap();
+
+        for line in ["line 1\n", "line 2\n", "line 3\n", "line 4\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        drop(writer);
+
+        assert_eq!(
+            file_names(&dir),
+            ["app.2.log.gz", "app.3.log.gz", "app.log"]
+        );
+        let mut decoder =
+            flate2::read::GzDecoder::new(File::open(dir.join("app.3.log.gz")).unwrap());
+        let mut content = String::new();
+        decoder.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "line 3\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_retention_by_total_size() {
+        let dir = temp_dir("total");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("app.1.log"), [b'x'; 40]).unwrap();
+        fs::write(dir.join("other.txt"), [b'x'; 40]).unwrap();
+        // File timestamps are not ordered across files written in quick succession
+        File::options()
+            .write(true)
+            .open(dir.join("app.1.log"))
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+
+        let mut writer = RollingFileWriter::builder()
+            .rotation(LogRollingRotation::Never)
+            .filename_prefix("app")
+            .filename_suffix("log")
+            .max_bytes(40)
+            .max_total_bytes(60)
+            .build(&dir)
+            .unwrap();
+        writer.write_all(&[b'y'; 30]).unwrap();
+        writer.write_all(&[b'z'; 30]).unwrap();
+        drop(writer);
+
+        // The pre-existing file is too large to keep next to the newer ones.
+        assert_eq!(file_names(&dir), ["app.2.log", "app.log", "other.txt"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_configuration() {
+        let dir = temp_dir("invalid");
+        assert!(RollingFileWriter::builder()
+            .rotation(LogRollingRotation::Never)
+            .build(&dir)
+            .is_err());
+        assert!(RollingFileWriter::builder()
+            .filename_prefix("app")
+            .max_bytes(0)
+            .build(&dir)
+            .is_err());
+    }
+
+    fn config(rotation: LogRollingRotation, prefix: &str, suffix: &str) -> RollingConfig {
+        RollingConfig {
+            dir: temp_dir("names"),
+            rotation,
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            max_bytes: None,
+            retention: Retention {
+                max_log_files: Some(1),
+                max_total_bytes: None,
+                max_age: None,
+                compression: LogCompression::None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_file_name_matching() {
+        let app = config(LogRollingRotation::Daily, "app", "log");
+        assert_eq!(
+            app.parse_file_name("app.2024-01-01.log"),
+            Some(("app.2024-01-01.log", Some("2024-01-01"), None))
+        );
+        assert_eq!(
+            app.parse_file_name("app.2024-01-01.3.log.gz"),
+            Some(("app.2024-01-01.3.log", Some("2024-01-01"), Some(3)))
+        );
+        assert_eq!(app.parse_file_name("app.error.2024-01-01.log"), None);
+        assert_eq!(app.parse_file_name("app.2024-01-01.error.log"), None);
+        assert_eq!(app.parse_file_name("app.2024-01-01-00.log"), None);
+        assert_eq!(app.parse_file_name("apple.2024-01-01.log"), None);
+        assert_eq!(app.parse_file_name("app.2024-01-01.log.bak"), None);
+
+        let unprefixed = config(LogRollingRotation::Never, "", "log");
+        assert_eq!(
+            unprefixed.parse_file_name("2.log"),
+            Some(("2.log", None, Some(2)))
+        );
+        assert_eq!(unprefixed.parse_file_name("app.log"), None);
+        assert_eq!(unprefixed.parse_file_name("app.2.log"), None);
+
+        let unsuffixed = config(LogRollingRotation::Hourly, "app", "");
+        assert_eq!(
+            unsuffixed.parse_file_name("app.2024-01-01-00.1"),
+            Some(("app.2024-01-01-00.1", Some("2024-01-01-00"), Some(1)))
+        );
+        assert_eq!(unsuffixed.parse_file_name("app.2024-01-01-00.json"), None);
+    }
+
+    #[test]
+    fn test_prune_skips_other_writers() {
+        let dir = temp_dir("others");
+        fs::create_dir_all(&dir).unwrap();
+        let names = ["app.1.log", "app.error.log", "app.error.1.log", "other.log"];
+        for name in names {
+            fs::write(dir.join(name), "old\n").unwrap();
+        }
+
+        let writer = RollingFileWriter::builder()
+            .rotation(LogRollingRotation::Never)
+            .filename_prefix("app")
+            .filename_suffix("log")
+            .max_log_files(1)
+            .build(&dir)
+            .unwrap();
+        drop(writer);
+
+        assert_eq!(
+            file_names(&dir),
+            ["app.error.1.log", "app.error.log", "app.log", "other.log"]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_worker_failures_are_reported() {
+        let config = RollingConfig {
+            dir: temp_dir("missing"),
+            rotation: LogRollingRotation::Never,
+            prefix: "app".to_string(),
+            suffix: "log".to_string(),
+            max_bytes: None,
+            retention: Retention {
+                max_log_files: Some(1),
+                max_total_bytes: None,
+                max_age: None,
+                compression: LogCompression::None,
+            },
+        };
+        let (sender, receiver) = mpsc::channel();
+        sender
+            .send(Job::Prune {
+                current: config.current_path(None),
+            })
+            .unwrap();
+        drop(sender);
+
+        let dir = temp_dir("reported");
+        fs::create_dir_all(&dir).unwrap();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(File::create(dir.join("output.log")).unwrap())
+            .with_ansi(false)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || config.run_worker(receiver));
+
+        let output = fs::read_to_string(dir.join("output.log")).unwrap();
+        assert!(output.contains("ERROR"), "{output}");
+        assert!(output.contains("Failed to prune log files"), "{output}");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_report_is_not_reentrant() {
+        let mut reported = 0;
+        report(|| {
+            reported += 1;
+            report(|| reported += 1);
+        });
+        report(|| reported += 1);
+        assert_eq!(reported, 2);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::logs::format::{insert_ids, JsonObject, OtelIds};
+use serde_json::value::RawValue;
+
+/// The ECS version written in the `ecs.version` field.
+pub const ECS_VERSION: &str = "8.11.0";
+
+/// The JSON layout expected by a log backend.
+///
+/// Every schema maps the timestamp, level, message, target, event fields, span fields and
+/// OpenTelemetry ids of the generic `tracing-subscriber` JSON output to the keys the backend
+/// understands without remapping rules. Span fields are flattened from the root span to the
+/// current one, inner spans and the event fields win on conflicts.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum JsonSchema {
+    /// The `tracing-subscriber` JSON layout with top-level `trace_id`, `span_id` and
+    /// `trace_flags`.
+    #[default]
+    Generic,
+    /// [Elastic Common Schema](https://www.elastic.co/guide/en/ecs-logging/overview/current/intro.html),
+    /// with `@timestamp`, `log.level`, `log.logger`, `trace.id` and `span.id`.
+    Ecs,
+    /// [Google Cloud Logging](https://cloud.google.com/logging/docs/structured-logging),
+    /// with `severity`, `logging.googleapis.com/trace` and `logging.googleapis.com/spanId`.
+    ///
+    /// Cloud Logging only links an entry to Cloud Trace when the trace is written as
+    /// `projects/{project_id}/traces/{trace_id}`, so set the project id when it is known.
+    Gcp { project_id: Option<String> },
+    /// [Datadog](https://docs.datadoghq.com/tracing/other_telemetry/connect_logs_and_traces/opentelemetry/),
+    /// with `status` and the `dd.trace_id` and `dd.span_id` correlation fields.
+    Datadog,
+}
+
+impl JsonSchema {
+    /// Map a generic JSON log line to this schema.
+    pub(crate) fn apply(&self, object: JsonObject, ids: Option<&OtelIds>) -> JsonObject {
+        match self {
+            Self::Generic => {
+                let mut object = object;
+                if let Some(ids) = ids {
+                    insert_ids(&mut object, ids);
+                }
+                object
+            }
+            Self::Ecs => GenericEvent::parse(object).into_ecs(ids),
+            Self::Gcp { project_id } => {
+                GenericEvent::parse(object).into_gcp(ids, project_id.as_deref())
+            }
+            Self::Datadog => GenericEvent::parse(object).into_datadog(ids),
+        }
+    }
+}
+
+/// The parts of a generic `tracing-subscriber` JSON log line.
+struct GenericEvent {
+    timestamp: Option<Box<RawValue>>,
+    level: String,
+    message: Option<Box<RawValue>>,
+    target: Option<Box<RawValue>>,
+    file: Option<Box<RawValue>>,
+    line: Option<Box<RawValue>>,
+    fields: JsonObject,
+}
+
+impl GenericEvent {
+    fn parse(mut object: JsonObject) -> Self {
+        let timestamp = object.remove("timestamp");
+        let level = object
+            .remove("level")
+            .and_then(|level| serde_json::from_str(level.get()).ok())
+            .unwrap_or_else(|| "INFO".to_string());
+        let message = object.remove("message");
+        let target = object.remove("target");
+        let file = object.remove("filename");
+        let line = object.remove("line_number");
+
+        let mut fields = JsonObject::default();
+        let spans: Vec<JsonObject> = match (object.remove("spans"), object.remove("span")) {
+            (Some(spans), _) => serde_json::from_str(spans.get()).unwrap_or_default(),
+            (_, Some(span)) => {
+                serde_json::from_str(span.get()).map_or_else(|_| Vec::new(), |span| vec![span])
+            }
+            _ => Vec::new(),
+        };
+        for mut span in spans {
+            span.remove("name");
+            fields.extend(span);
+        }
+        fields.extend(object);
+
+        Self {
+            timestamp,
+            level,
+            message,
+            target,
+            file,
+            line,
+            fields,
+        }
+    }
+
+    fn into_ecs(self, ids: Option<&OtelIds>) -> JsonObject {
+        let mut object = JsonObject::default();
+        insert(&mut object, "@timestamp", self.timestamp);
+        object.insert_value("log.level", self.level.to_lowercase());
+        insert(&mut object, "message", self.message);
+        object.insert_value("ecs.version", ECS_VERSION);
+        insert(&mut object, "log.logger", self.target);
+        insert(&mut object, "log.origin.file.name", self.file);
+        insert(&mut object, "log.origin.file.line", self.line);
+        object.extend(self.fields);
+        if let Some(ids) = ids {
+            object.insert_value("trace.id", ids.trace_id.to_string());
+            object.insert_value("span.id", ids.span_id.to_string());
+        }
+        object
+    }
+
+    fn into_gcp(self, ids: Option<&OtelIds>, project_id: Option<&str>) -> JsonObject {
+        let severity = match self.level.as_str() {
+            "TRACE" | "DEBUG" => "DEBUG",
+            "WARN" => "WARNING",
+            "ERROR" => "ERROR",
+            _ => "INFO",
+        };
+
+        let mut object = JsonObject::default();
+        insert(&mut object, "time", self.timestamp);
+        object.insert_value("severity", severity);
+        insert(&mut object, "message", self.message);
+        insert(&mut object, "target", self.target);
+        if self.file.is_some() || self.line.is_some() {
+            let mut location = JsonObject::default();
+            insert(&mut location, "file", self.file);
+            // Cloud Logging expects the line as a string.
+            if let Some(line) = self.line {
+                location.insert_value("line", line.get());
+            }
+            object.insert_value("logging.googleapis.com/sourceLocation", location);
+        }
+        object.extend(self.fields);
+        if let Some(ids) = ids {
+            let trace = match project_id {
+                Some(project_id) => format!("projects/{project_id}/traces/{}", ids.trace_id),
+                None => ids.trace_id.to_string(),
+            };
+            object.insert_value("logging.googleapis.com/trace", trace);
+            object.insert_value("logging.googleapis.com/spanId", ids.span_id.to_string());
+            object.insert_value(
+                "logging.googleapis.com/trace_sampled",
+                ids.trace_flags.is_sampled(),
+            );
+        }
+        object
+    }
+
+    fn into_datadog(self, ids: Option<&OtelIds>) -> JsonObject {
+        let status = match self.level.as_str() {
+            "TRACE" | "DEBUG" => "debug",
+            "WARN" => "warn",
+            "ERROR" => "error",
+            _ => "info",
+        };
+
+        let mut object = JsonObject::default();
+        insert(&mut object, "timestamp", self.timestamp);
+        object.insert_value("status", status);
+        insert(&mut object, "message", self.message);
+        insert(&mut object, "logger.name", self.target);
+        object.extend(self.fields);
+        if let Some(ids) = ids {
+            // Datadog correlates on the lower 64 bits of the trace id, in decimal.
+            let trace_id = ids.trace_id.to_bytes();
+            let trace_id = u64::from_be_bytes(trace_id[8..].try_into().unwrap_or_default());
+            let span_id = u64::from_be_bytes(ids.span_id.to_bytes());
+            object.insert_value("dd.trace_id", trace_id.to_string());
+            object.insert_value("dd.span_id", span_id.to_string());
+        }
+        object
+    }
+}
+
+fn insert(object: &mut JsonObject, key: &str, value: Option<Box<RawValue>>) {
+    if let Some(value) = value {
+        object.insert(key, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::otel::opentelemetry::trace::{SpanId, TraceFlags, TraceId};
+    use serde_json::{json, Value};
+
+    fn generic() -> JsonObject {
+        JsonObject::parse(
+            &json!({
+                "timestamp": "2025-01-01T00:00:00.000000Z",
+                "level": "WARN",
+                "message": "job failed",
+                "reason": "disk full",
+                "target": "worker",
+                "filename": "src/worker.rs",
+                "line_number": 42,
+                "span": {"attempt": 2, "name": "job"},
+                "spans": [{"tenant": "acme", "name": "request"}, {"attempt": 2, "name": "job"}],
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn apply(schema: JsonSchema, ids: Option<&OtelIds>) -> Value {
+        serde_json::to_value(schema.apply(generic(), ids)).unwrap()
+    }
+
+    fn ids() -> OtelIds {
+        OtelIds {
+            trace_id: TraceId::from_hex("0af7651916cd43dd8448eb211c80319c").unwrap(),
+            span_id: SpanId::from_hex("b7ad6b7169203331").unwrap(),
+            trace_flags: TraceFlags::SAMPLED,
+        }
+    }
+
+    #[test]
+    fn test_generic_schema() {
+        let object = apply(JsonSchema::Generic, Some(&ids()));
+        assert_eq!(object["trace_id"], "0af7651916cd43dd8448eb211c80319c");
+        assert_eq!(object["span_id"], "b7ad6b7169203331");
+        assert_eq!(object["trace_flags"], "01");
+        assert_eq!(object["spans"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_ecs_schema() {
+        let object = apply(JsonSchema::Ecs, Some(&ids()));
+        assert_eq!(
+            object,
+            json!({
+                "@timestamp": "2025-01-01T00:00:00.000000Z",
+                "log.level": "warn",
+                "message": "job failed",
+                "ecs.version": ECS_VERSION,
+                "log.logger": "worker",
+                "log.origin.file.name": "src/worker.rs",
+                "log.origin.file.line": 42,
+                "tenant": "acme",
+                "attempt": 2,
+                "reason": "disk full",
+                "trace.id": "0af7651916cd43dd8448eb211c80319c",
+                "span.id": "b7ad6b7169203331",
+            })
+        );
+    }
+
+    #[test]
+    fn test_gcp_schema() {
+        let schema = JsonSchema::Gcp {
+            project_id: Some("my-project".to_string()),
+        };
+        let object = apply(schema, Some(&ids()));
+        assert_eq!(object["severity"], "WARNING");
+        assert_eq!(object["time"], "2025-01-01T00:00:00.000000Z");
+        assert_eq!(object["message"], "job failed");
+        assert_eq!(object["tenant"], "acme");
+        assert_eq!(
+            object["logging.googleapis.com/sourceLocation"],
+            json!({"file": "src/worker.rs", "line": "42"})
+        );
+        assert_eq!(
+            object["logging.googleapis.com/trace"],
+            "projects/my-project/traces/0af7651916cd43dd8448eb211c80319c"
+        );
+        assert_eq!(object["logging.googleapis.com/spanId"], "b7ad6b7169203331");
+        assert_eq!(object["logging.googleapis.com/trace_sampled"], true);
+
+        let object = apply(JsonSchema::Gcp { project_id: None }, None);
+        assert!(object.get("logging.googleapis.com/trace").is_none());
+    }
+
+    #[test]
+    fn test_datadog_schema() {
+        let object = apply(JsonSchema::Datadog, Some(&ids()));
+        assert_eq!(object["status"], "warn");
+        assert_eq!(object["logger.name"], "worker");
+        assert_eq!(object["attempt"], 2);
+        assert_eq!(
+            object["dd.trace_id"],
+            u64::from_str_radix("8448eb211c80319c", 16)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            object["dd.span_id"],
+            u64::from_str_radix("b7ad6b7169203331", 16)
+                .unwrap()
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Graceful shutdown of a server and its telemetry.
+//!
+//! [`GracefulShutdown`] owns the [`LoggerGuard`]. Its [`signal`](GracefulShutdown::signal)
+//! future completes on `SIGINT` or `SIGTERM`, which makes the server stop accepting
+//! connections and drain the in-flight requests. Once the server has drained,
+//! [`finish`](GracefulShutdown::finish) flushes the spans, metrics and logs within a deadline,
+//! so that a collector which is down cannot keep the process from exiting.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use tracing_otel_extra::{GracefulShutdown, Logger};
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let guard = Logger::new("my-service").init()?;
+//!     let shutdown = GracefulShutdown::new(guard).with_timeout(Duration::from_secs(10));
+//!
+//!     let app = axum::Router::new();
+//!     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+//!     axum::serve(listener, app)
+//!         .with_graceful_shutdown(shutdown.signal())
+//!         .await?;
+//!
+//!     shutdown.finish().await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    logs::LoggerGuard,
+    otel::{ProviderErrors, DEFAULT_SHUTDOWN_TIMEOUT},
+};
+use std::{future::Future, time::Duration};
+
+/// Owns the [`LoggerGuard`] of a server until it has drained, see the
+/// [module documentation](self).
+///
+/// Dropping it without calling [`finish`](GracefulShutdown::finish) drops the guard, which
+/// shuts down the providers with the [`DEFAULT_SHUTDOWN_TIMEOUT`].
+#[derive(Debug)]
+pub struct GracefulShutdown {
+    guard: LoggerGuard,
+    timeout: Duration,
+}
+
+impl GracefulShutdown {
+    /// Take ownership of the guard, an [`OtelGuard`](crate::otel::OtelGuard) or a
+    /// [`LoggerGuard`].
+    pub fn new(guard: impl Into<LoggerGuard>) -> Self {
+        Self {
+            guard: guard.into(),
+            timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+        }
+    }
+
+    /// Set the deadline for flushing and shutting down the providers.
+    /// Defaults to [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The guard, to read the providers or the dropped lines while the server runs.
+    pub fn guard(&self) -> &LoggerGuard {
+        &self.guard
+    }
+
+    /// A future which completes on `SIGINT` (Ctrl+C) or, on Unix, `SIGTERM`, for
+    /// `axum::serve(..).with_graceful_shutdown`.
+    pub fn signal(&self) -> impl Future<Output = ()> + Send + 'static {
+        async {
+            let signal = shutdown_signal().await;
+            tracing::info!(
+                signal,
+                "Shutdown signal received, draining in-flight requests"
+            );
+        }
+    }
+
+    /// Flush the spans, metrics and logs once the server has drained.
+    ///
+    /// The providers are shut down within the deadline, then the non-blocking writers are
+    /// flushed. The errors of the providers which failed or missed the deadline are logged
+    /// and returned.
+    pub async fn finish(self) -> Result<(), ProviderErrors> {
+        let Self { mut guard, timeout } = self;
+        tracing::info!(?timeout, "Server drained, flushing telemetry");
+        let shutdown = tokio::task::spawn_blocking(move || {
+            let result = guard.shutdown_providers(timeout);
+            match &result {
+                Ok(()) => tracing::info!("Telemetry flushed"),
+                Err(err) => tracing::error!("Failed to flush telemetry: {err}"),
+            }
+            // the writers are flushed last, so that the messages above are written
+            drop(guard);
+            result
+        });
+        match shutdown.await {
+            Ok(result) => result,
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+}
+
+// The name of the signal received
+async fn shutdown_signal() -> &'static str {
+    let ctrl_c = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "SIGINT",
+            Err(err) => {
+                tracing::error!("Failed to listen for Ctrl+C: {err}");
+                std::future::pending().await
+            }
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+                "SIGTERM"
+            }
+            Err(err) => {
+                tracing::error!("Failed to listen for SIGTERM: {err}");
+                std::future::pending().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<&'static str>();
+
+    tokio::select! {
+        signal = ctrl_c => signal,
+        signal = terminate => signal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::otel::{
+        opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider},
+        OtelGuard,
+    };
+
+    #[tokio::test]
+    async fn test_finish_shuts_down_the_providers() {
+        let reader = PeriodicReader::builder(InMemoryMetricExporter::default()).build();
+        let meter_provider = SdkMeterProvider::builder().with_reader(reader).build();
+        let shutdown = GracefulShutdown::new(OtelGuard::new(None, Some(meter_provider.clone())))
+            .with_timeout(Duration::from_secs(1));
+        assert!(shutdown.guard().meter_provider().is_some());
+
+        shutdown.finish().await.unwrap();
+        // already shut down by the guard
+        assert!(meter_provider.shutdown().is_err());
+    }
+}
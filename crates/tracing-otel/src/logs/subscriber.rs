@@ -1,27 +1,59 @@
 use crate::{
-    logs::{LogFormat, Logger},
+    logs::{
+        AppenderGuard, ConsoleTarget, JsonOptions, JsonSchema, LogFormat, LogfmtFields,
+        LogfmtFormat, Logger, LoggerFileAppender, OtelFormat, SpanFieldPrefix,
+    },
     otel::{
         get_resource, init_meter_provider, init_tracer_provider, init_tracing_subscriber,
         opentelemetry::KeyValue, OtelGuard,
     },
 };
-use anyhow::{anyhow, Context, Result};
-use std::sync::OnceLock;
+use anyhow::{Context, Result};
 use tracing::Level;
-use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::non_blocking::{NonBlocking, NonBlockingBuilder};
 use tracing_opentelemetry_extra::BoxLayer;
 use tracing_subscriber::{
-    fmt::{self, format::FmtSpan, MakeWriter},
+    fmt::{
+        self,
+        format::FmtSpan,
+        writer::{MakeWriterExt, OrElse, WithMaxLevel},
+        MakeWriter,
+    },
     EnvFilter, Layer, Registry,
 };
 
-// Keep non-blocking appender worker guard to prevent log loss
-static NONBLOCKING_APPENDER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+// Wrap a writer in a non-blocking writer, keeping its worker guard.
+// A lossy writer drops lines when its buffer is full, otherwise it blocks until there is room.
+fn non_blocking<W>(
+    name: &str,
+    writer: W,
+    buffered_lines_limit: Option<usize>,
+    lossy: bool,
+    guards: &mut Vec<AppenderGuard>,
+) -> NonBlocking
+where
+    W: std::io::Write + Send + 'static,
+{
+    let mut builder = NonBlockingBuilder::default().lossy(lossy);
+    if let Some(limit) = buffered_lines_limit {
+        builder = builder.buffered_lines_limit(limit);
+    }
+    let (non_blocking, guard) = builder.finish(writer);
+    guards.push(AppenderGuard::new(
+        name,
+        guard,
+        non_blocking.error_counter(),
+    ));
+    non_blocking
+}
 
-pub fn set_nonblocking_appender_guard(guard: WorkerGuard) -> Result<()> {
-    NONBLOCKING_APPENDER_GUARD
-        .set(guard)
-        .map_err(|_| anyhow!("cannot lock for appender"))
+/// Route WARN and ERROR events to `stderr` and all other events to `stdout`.
+pub fn split_by_level<E, O>(stderr: E, stdout: O) -> OrElse<WithMaxLevel<E>, O>
+where
+    E: for<'writer> MakeWriter<'writer>,
+    O: for<'writer> MakeWriter<'writer>,
+{
+    stderr.with_max_level(Level::WARN).or_else(stdout)
 }
 
 /// Creates an environment filter for tracing based on the given level.
@@ -45,25 +77,66 @@ pub fn init_env_filter(level: &Level) -> EnvFilter {
     EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level.to_string()))
 }
 
-/// Apply the specified format to a tracing layer
+/// Apply the specified format to a tracing layer.
+///
+/// Every format is wrapped in [`OtelFormat`], which adds the OpenTelemetry ids of the
+/// current span to each line.
 fn apply_layer_format<N, W>(
     layer: fmt::Layer<Registry, N, fmt::format::Format, W>,
     format: &LogFormat,
+    json: &JsonOptions,
+    span_field_prefix: &SpanFieldPrefix,
 ) -> Box<dyn Layer<Registry> + Sync + Send>
 where
     N: for<'writer> fmt::format::FormatFields<'writer> + Sync + Send + 'static,
     W: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
 {
     match format {
-        LogFormat::Compact => layer.compact().boxed(),
-        LogFormat::Pretty => layer.pretty().boxed(),
-        LogFormat::Json => layer
-            .event_format(fmt::format().json().flatten_event(true))
-            .fmt_fields(fmt::format::JsonFields::new())
+        LogFormat::Compact => layer
+            .compact()
+            .map_event_format(OtelFormat::compact)
+            .boxed(),
+        LogFormat::Pretty => layer.pretty().map_event_format(OtelFormat::pretty).boxed(),
+        LogFormat::Json => json_layer(layer, json, JsonSchema::Generic),
+        LogFormat::Ecs => json_layer(layer, json, JsonSchema::Ecs),
+        LogFormat::Gcp => json_layer(
+            layer,
+            json,
+            JsonSchema::Gcp {
+                project_id: std::env::var("GOOGLE_CLOUD_PROJECT").ok(),
+            },
+        ),
+        LogFormat::Datadog => json_layer(layer, json, JsonSchema::Datadog),
+        LogFormat::Logfmt => layer
+            .fmt_fields(LogfmtFields::new())
+            .event_format(LogfmtFormat::new().with_span_field_prefix(span_field_prefix.clone()))
             .boxed(),
     }
 }
 
+/// Build a JSON layer with the given options and schema.
+///
+/// The vendor schemas read the event fields from the top level, so they always flatten them.
+fn json_layer<N, W>(
+    layer: fmt::Layer<Registry, N, fmt::format::Format, W>,
+    options: &JsonOptions,
+    schema: JsonSchema,
+) -> Box<dyn Layer<Registry> + Sync + Send>
+where
+    N: for<'writer> fmt::format::FormatFields<'writer> + Sync + Send + 'static,
+    W: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
+{
+    let format = if schema == JsonSchema::Generic {
+        OtelFormat::from_json_options(options)
+    } else {
+        OtelFormat::from_json_options(&options.clone().with_flatten_event(true)).with_schema(schema)
+    };
+    layer
+        .event_format(format)
+        .fmt_fields(fmt::format::JsonFields::new())
+        .boxed()
+}
+
 /// Initialize a format layer with the given writer and format
 pub fn init_layer<W2>(
     writer: W2,
@@ -73,69 +146,214 @@ pub fn init_layer<W2>(
 ) -> Box<dyn Layer<Registry> + Sync + Send>
 where
     W2: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
+{
+    init_layer_with_json_options(writer, format, &JsonOptions::default(), span_events, ansi)
+}
+
+/// Like [`init_layer`], with the layout options of the JSON formats.
+pub fn init_layer_with_json_options<W2>(
+    writer: W2,
+    format: &LogFormat,
+    json: &JsonOptions,
+    span_events: FmtSpan,
+    ansi: bool,
+) -> Box<dyn Layer<Registry> + Sync + Send>
+where
+    W2: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
+{
+    format_layer(
+        writer,
+        format,
+        json,
+        &SpanFieldPrefix::default(),
+        span_events,
+        ansi,
+    )
+}
+
+// Initialize a format layer with the output settings of a logger
+fn logger_layer<W>(
+    logger: &Logger,
+    writer: W,
+    format: &LogFormat,
+    json: &JsonOptions,
+    ansi: bool,
+) -> BoxLayer
+where
+    W: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
+{
+    format_layer(
+        writer,
+        format,
+        json,
+        &logger.logfmt_span_field_prefix,
+        logger.span_events.clone(),
+        ansi,
+    )
+}
+
+fn format_layer<W>(
+    writer: W,
+    format: &LogFormat,
+    json: &JsonOptions,
+    span_field_prefix: &SpanFieldPrefix,
+    span_events: FmtSpan,
+    ansi: bool,
+) -> BoxLayer
+where
+    W: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
 {
     let layer = fmt::Layer::new()
         .with_writer(writer)
         .with_ansi(ansi)
         .with_span_events(span_events);
-    apply_layer_format(layer, format)
+    apply_layer_format(layer, format, json, span_field_prefix)
+}
+
+/// The output layers of a [`Logger`] and the worker guards of their non-blocking writers.
+pub struct OutputLayers {
+    pub layers: Vec<BoxLayer>,
+    pub guards: Vec<AppenderGuard>,
 }
 
 /// Create output layers based on configuration.
 ///
 /// This function creates output layers based on the provided configuration.
+/// The worker guards of non-blocking writers must be kept alive for as long as the layers
+/// are in use, and dropped to flush the writers.
 ///
 /// # Arguments
 ///
 /// * `console_enabled` - Whether to enable console output
-pub fn create_output_layers(logger: &Logger) -> Result<Vec<BoxLayer>> {
+pub fn create_output_layers(logger: &Logger) -> Result<OutputLayers> {
     let mut layers: Vec<BoxLayer> = vec![];
+    let mut guards = vec![];
 
     // Add console layer if enabled
     if logger.console_enabled {
-        let stdout_layer = init_layer(
-            std::io::stdout,
-            &logger.format,
-            logger.span_events.clone(),
-            logger.ansi,
+        layers.push(console_layer(logger, &mut guards));
+    }
+    // Add a layer for each configured and enabled file appender
+    for config in logger.all_file_appenders().filter(|config| config.enable) {
+        layers.push(file_layer(logger, config, &mut guards)?);
+    }
+    // Add syslog layer if configured and enabled
+    if let Some(config) = logger.syslog.as_ref().filter(|config| config.enable) {
+        let writer = config.build_writer(&logger.service_name)?;
+        let layer = logger_layer(
+            logger,
+            writer,
+            &config.format.clone().unwrap_or_default(),
+            &logger.json,
+            false,
         );
-        layers.push(stdout_layer);
+        layers.push(with_filter(layer, config.filter.as_deref())?);
     }
-    // Add file layer if configured and enabled
-    if let Some(config) = &logger.file_appender {
-        if config.enable {
-            let rolling_builder = tracing_appender::rolling::Builder::new()
-                .max_log_files(config.max_log_files)
-                .rotation(config.get_rolling_rotation());
-
-            let file_appender = rolling_builder
-                .filename_prefix(config.filename_prefix_or_default())
-                .filename_suffix(config.filename_suffix_or_default())
-                .build(config.dir_or_default())
-                .context("Failed to build file appender")?;
-
-            let file_appender_layer = if config.non_blocking {
-                let (non_blocking_file_appender, work_guard) =
-                    tracing_appender::non_blocking(file_appender);
-                set_nonblocking_appender_guard(work_guard)?;
-                init_layer(
-                    non_blocking_file_appender,
-                    &config.format_or_default(),
-                    logger.span_events.clone(),
-                    config.ansi,
-                )
-            } else {
-                init_layer(
-                    file_appender,
-                    &config.format_or_default(),
-                    logger.span_events.clone(),
-                    config.ansi,
-                )
-            };
-            layers.push(file_appender_layer);
+    // Add journald layer if configured and enabled
+    if let Some(config) = logger.journald.as_ref().filter(|config| config.enable) {
+        let layer = config.build_layer(&logger.service_name)?.boxed();
+        layers.push(with_filter(layer, config.filter.as_deref())?);
+    }
+    Ok(OutputLayers { layers, guards })
+}
+
+/// Parse a directive filter in `RUST_LOG` syntax.
+pub(crate) fn parse_filter(filter: &str) -> Result<EnvFilter> {
+    EnvFilter::builder()
+        .parse(filter)
+        .with_context(|| format!("Invalid filter: '{filter}'"))
+}
+
+// Apply an optional directive filter to a layer
+fn with_filter(layer: BoxLayer, filter: Option<&str>) -> Result<BoxLayer> {
+    Ok(match filter {
+        Some(filter) => layer.with_filter(parse_filter(filter)?).boxed(),
+        None => layer,
+    })
+}
+
+/// Create the console layer, writing to the configured target.
+fn console_layer(logger: &Logger, guards: &mut Vec<AppenderGuard>) -> BoxLayer {
+    use std::io::{stderr, stdout};
+
+    let limit = logger.console_buffered_lines_limit;
+    let lossy = logger.console_lossy;
+
+    match (logger.console_target, logger.console_non_blocking) {
+        (ConsoleTarget::Stdout, false) => console_writer_layer(logger, stdout),
+        (ConsoleTarget::Stderr, false) => console_writer_layer(logger, stderr),
+        (ConsoleTarget::Split, false) => {
+            console_writer_layer(logger, split_by_level(stderr, stdout))
+        }
+        (ConsoleTarget::Stdout, true) => console_writer_layer(
+            logger,
+            non_blocking("stdout", stdout(), limit, lossy, guards),
+        ),
+        (ConsoleTarget::Stderr, true) => console_writer_layer(
+            logger,
+            non_blocking("stderr", stderr(), limit, lossy, guards),
+        ),
+        (ConsoleTarget::Split, true) => {
+            let stderr = non_blocking("stderr", stderr(), limit, lossy, guards);
+            let stdout = non_blocking("stdout", stdout(), limit, lossy, guards);
+            console_writer_layer(logger, split_by_level(stderr, stdout))
         }
     }
-    Ok(layers)
+}
+
+fn console_writer_layer<W>(logger: &Logger, writer: W) -> BoxLayer
+where
+    W: for<'writer> MakeWriter<'writer> + Sync + Send + 'static,
+{
+    logger_layer(logger, writer, &logger.format, &logger.json, logger.ansi)
+}
+
+/// Create the layer of a file appender, filtered by its own filter or level.
+fn file_layer(
+    logger: &Logger,
+    config: &LoggerFileAppender,
+    guards: &mut Vec<AppenderGuard>,
+) -> Result<BoxLayer> {
+    let file_appender = config
+        .build_writer()
+        .context("Failed to build file appender")?;
+    let format = config.format_or_default();
+    let json = config.json.as_ref().unwrap_or(&logger.json);
+
+    let layer = if config.non_blocking {
+        logger_layer(
+            logger,
+            non_blocking(
+                &config.filename_prefix_or_default(),
+                file_appender,
+                config.buffered_lines_limit,
+                config.lossy,
+                guards,
+            ),
+            &format,
+            json,
+            config.ansi,
+        )
+    } else {
+        logger_layer(logger, file_appender, &format, json, config.ansi)
+    };
+    Ok(match config.build_filter()? {
+        Some(filter) => layer.with_filter(filter).boxed(),
+        None => layer,
+    })
+}
+
+/// Initializes the tracing subscriber with the output layers only, without OpenTelemetry.
+///
+/// No tracer or meter provider is created, so nothing is exported and no background
+/// exporter tasks are started.
+pub fn setup_logging(level: Level, layers: Vec<BoxLayer>) {
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(init_env_filter(&level))
+        .init();
 }
 
 /// Initializes the complete tracing stack with OpenTelemetry integration.
@@ -187,7 +405,7 @@ pub fn create_output_layers(logger: &Logger) -> Result<Vec<BoxLayer>> {
 ///     // Your application code here...
 ///
 ///     // Cleanup when done
-///     guard.shutdown()?;
+///     guard.shutdown_with_timeout(std::time::Duration::from_secs(5))?;
 ///     Ok(())
 /// }
 /// ```
@@ -214,3 +432,92 @@ pub fn setup_tracing(
 
     Ok(guard)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_split_by_level() {
+        let dir = std::env::temp_dir().join(format!("tracing-otel-split-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let stderr = std::fs::File::create(dir.join("stderr")).unwrap();
+        let stdout = std::fs::File::create(dir.join("stdout")).unwrap();
+        let layer = fmt::layer()
+            .with_writer(split_by_level(stderr, stdout))
+            .with_ansi(false);
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::info!("started");
+            tracing::warn!("slow");
+            tracing::error!("failed");
+        });
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        let (stderr, stdout) = (read("stderr"), read("stdout"));
+        assert!(stdout.contains("started") && !stdout.contains("slow"));
+        assert!(stderr.contains("slow") && stderr.contains("failed"));
+        assert!(!stderr.contains("started"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_appenders_with_filters() {
+        let dir =
+            std::env::temp_dir().join(format!("tracing-otel-appenders-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let appender = |name: &str, filter: &str| {
+            LoggerFileAppender::new(name)
+                .with_filter(filter)
+                .with_rotation(crate::logs::LogRollingRotation::Never)
+                .with_dir(dir.to_string_lossy())
+        };
+        let logger = Logger::new("test-service")
+            .with_console_enabled(false)
+            .with_file_appenders(vec![
+                appender("app", "info"),
+                appender("error", "warn"),
+                appender("audit", "audit=info"),
+            ]);
+
+        let layers = create_output_layers(&logger).unwrap().layers;
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layers), || {
+            tracing::info!("started");
+            tracing::error!("failed");
+            tracing::info!(target: "audit", "user deleted");
+        });
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        let app = read("app.log");
+        assert!(app.contains("started") && app.contains("failed") && app.contains("user deleted"));
+        let error = read("error.log");
+        assert!(error.contains("failed") && !error.contains("started"));
+        let audit = read("audit.log");
+        assert!(audit.contains("user deleted") && !audit.contains("failed"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_logfmt_span_field_prefix() {
+        let dir = std::env::temp_dir().join(format!("tracing-otel-logfmt-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let logger = Logger::new("test-service")
+            .with_console_enabled(false)
+            .with_logfmt_span_field_prefix(SpanFieldPrefix::SpanName)
+            .with_file_appenders(vec![LoggerFileAppender::new("app")
+                .with_format(LogFormat::Logfmt)
+                .with_rotation(crate::logs::LogRollingRotation::Never)
+                .with_dir(dir.to_string_lossy())]);
+
+        let layers = create_output_layers(&logger).unwrap().layers;
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layers), || {
+            let span = tracing::info_span!("job", attempt = 2);
+            let _entered = span.enter();
+            tracing::info!("started");
+        });
+
+        let app = std::fs::read_to_string(dir.join("app.log")).unwrap();
+        assert!(app.contains(" job.attempt=2"), "{app}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,603 @@
+use crate::logs::layer::LogFormat;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    net::{TcpStream, ToSocketAddrs, UdpSocket},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::{
+    format::Writer,
+    time::{FormatTime, SystemTime},
+    MakeWriter,
+};
+
+/// The default syslog socket of Unix systems.
+pub const DEFAULT_SYSLOG_SOCKET: &str = "/dev/log";
+
+/// The default address of a remote syslog server.
+pub const DEFAULT_SYSLOG_ADDRESS: &str = "127.0.0.1:514";
+
+// The connect and write timeout of the TCP transport, which bounds how long a logging thread
+// can wait on an unreachable server
+const TCP_TIMEOUT: Duration = Duration::from_secs(1);
+// The delays between reconnection attempts, during which messages are dropped
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The transport used to reach the syslog daemon.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum SyslogTransport {
+    /// A Unix datagram socket, `/dev/log` by default.
+    #[serde(rename = "unix")]
+    #[default]
+    Unix,
+    /// UDP, one message per datagram.
+    #[serde(rename = "udp")]
+    Udp,
+    /// TCP, with octet-counting framing as described in RFC 6587.
+    #[serde(rename = "tcp")]
+    Tcp,
+}
+
+/// The syslog facility of the messages.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogFacility {
+    Kern,
+    #[default]
+    User,
+    Mail,
+    Daemon,
+    Auth,
+    Syslog,
+    Lpr,
+    News,
+    Uucp,
+    Cron,
+    Authpriv,
+    Ftp,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    /// The numerical code of the facility.
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Kern => 0,
+            Self::User => 1,
+            Self::Mail => 2,
+            Self::Daemon => 3,
+            Self::Auth => 4,
+            Self::Syslog => 5,
+            Self::Lpr => 6,
+            Self::News => 7,
+            Self::Uucp => 8,
+            Self::Cron => 9,
+            Self::Authpriv => 10,
+            Self::Ftp => 11,
+            Self::Local0 => 16,
+            Self::Local1 => 17,
+            Self::Local2 => 18,
+            Self::Local3 => 19,
+            Self::Local4 => 20,
+            Self::Local5 => 21,
+            Self::Local6 => 22,
+            Self::Local7 => 23,
+        }
+    }
+}
+
+/// Map a `tracing` level to a syslog severity.
+///
+/// `ERROR` is `err` (3), `WARN` is `warning` (4), `INFO` is `info` (6), and `DEBUG` and
+/// `TRACE` are `debug` (7).
+pub fn syslog_severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        _ => 7,
+    }
+}
+
+/// Configuration of the syslog output.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SyslogConfig {
+    /// Enable the syslog output.
+    /// Defaults to true.
+    #[serde(default = "crate::logs::default::enable")]
+    pub enable: bool,
+
+    /// The transport used to reach the syslog daemon.
+    ///
+    /// * options: `unix` | `udp` | `tcp`
+    #[serde(default)]
+    pub transport: SyslogTransport,
+
+    /// The socket path for `unix`, or the `host:port` for `udp` and `tcp`.
+    /// Defaults to `/dev/log` and `127.0.0.1:514`.
+    #[serde(default)]
+    pub address: Option<String>,
+
+    /// The facility of the messages. Defaults to `user`.
+    #[serde(default)]
+    pub facility: SyslogFacility,
+
+    /// The APP-NAME of the messages. Defaults to the service name.
+    #[serde(default)]
+    pub app_name: Option<String>,
+
+    /// The HOSTNAME of the messages. Defaults to the host name of the machine.
+    #[serde(default)]
+    pub hostname: Option<String>,
+
+    /// The format of the message text. Defaults to `compact`.
+    #[serde(
+        default,
+        deserialize_with = "crate::logs::logger::deserialize_log_format_optional"
+    )]
+    pub format: Option<LogFormat>,
+
+    /// A directive filter in `RUST_LOG` syntax, such as `warn`.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+impl SyslogConfig {
+    /// Create an enabled syslog configuration for the given transport.
+    pub fn new(transport: SyslogTransport) -> Self {
+        Self {
+            enable: true,
+            transport,
+            address: None,
+            facility: SyslogFacility::default(),
+            app_name: None,
+            hostname: None,
+            format: None,
+            filter: None,
+        }
+    }
+
+    /// Set the socket path or `host:port` address.
+    pub fn with_address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Set the facility.
+    pub fn with_facility(mut self, facility: SyslogFacility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Set the APP-NAME.
+    pub fn with_app_name(mut self, app_name: impl Into<String>) -> Self {
+        self.app_name = Some(app_name.into());
+        self
+    }
+
+    /// Set the directive filter.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Connect to the syslog daemon, using `service_name` as the default APP-NAME.
+    pub fn build_writer(&self, service_name: &str) -> Result<SyslogWriter> {
+        let address = self
+            .address
+            .clone()
+            .unwrap_or_else(|| match self.transport {
+                SyslogTransport::Unix => DEFAULT_SYSLOG_SOCKET.to_string(),
+                _ => DEFAULT_SYSLOG_ADDRESS.to_string(),
+            });
+        let connection = Connection::connect(self.transport, &address)
+            .with_context(|| format!("Failed to connect to syslog at {address}"))?;
+        let app_name = self.app_name.as_deref().unwrap_or(service_name);
+        let hostname = self.hostname.clone().unwrap_or_else(hostname);
+
+        Ok(SyslogWriter {
+            inner: Arc::new(SyslogInner {
+                connection: Mutex::new(connection),
+                facility: self.facility,
+                hostname: header_field(&hostname, 255),
+                app_name: header_field(app_name, 48),
+                procid: std::process::id().to_string(),
+            }),
+        })
+    }
+}
+
+/// A [`MakeWriter`] which sends each formatted event to syslog as an RFC 5424 message.
+///
+/// The severity of a message is derived from the level of the event.
+///
+/// Over TCP, connecting and writing time out after one second. A lost connection is
+/// re-established on the next message, with an exponential backoff of up to 30 seconds
+/// between attempts during which messages are dropped.
+#[derive(Debug, Clone)]
+pub struct SyslogWriter {
+    inner: Arc<SyslogInner>,
+}
+
+#[derive(Debug)]
+struct SyslogInner {
+    connection: Mutex<Connection>,
+    facility: SyslogFacility,
+    hostname: String,
+    app_name: String,
+    procid: String,
+}
+
+#[derive(Debug)]
+enum Connection {
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(TcpConnection),
+}
+
+impl Connection {
+    fn connect(transport: SyslogTransport, address: &str) -> io::Result<Self> {
+        match transport {
+            #[cfg(unix)]
+            SyslogTransport::Unix => {
+                let socket = std::os::unix::net::UnixDatagram::unbound()?;
+                socket.connect(address)?;
+                Ok(Self::Unix(socket))
+            }
+            #[cfg(not(unix))]
+            SyslogTransport::Unix => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unix sockets are not supported on this platform",
+            )),
+            SyslogTransport::Udp => {
+                let socket = UdpSocket::bind(if address.starts_with('[') {
+                    "[::]:0"
+                } else {
+                    "0.0.0.0:0"
+                })?;
+                socket.connect(address)?;
+                Ok(Self::Udp(socket))
+            }
+            SyslogTransport::Tcp => Ok(Self::Tcp(TcpConnection {
+                address: address.to_string(),
+                stream: Some(connect_tcp(address)?),
+                backoff: MIN_RECONNECT_BACKOFF,
+                retry_at: None,
+            })),
+        }
+    }
+
+    fn send(&mut self, message: &[u8]) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(socket) => socket.send(message).map(|_| ()),
+            Self::Udp(socket) => socket.send(message).map(|_| ()),
+            Self::Tcp(connection) => {
+                let mut frame = format!("{} ", message.len()).into_bytes();
+                frame.extend_from_slice(message);
+                connection.send(&frame)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TcpConnection {
+    address: String,
+    stream: Option<TcpStream>,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+}
+
+impl TcpConnection {
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        // Reconnect once if the connection was lost.
+        for _ in 0..2 {
+            if self.stream.is_none() {
+                self.reconnect()?;
+            }
+            if let Some(stream) = &mut self.stream {
+                match stream.write_all(frame) {
+                    Ok(()) => return Ok(()),
+                    // A partly written frame cannot be resumed on the same stream
+                    Err(_) => self.stream = None,
+                }
+            }
+        }
+        Err(io::ErrorKind::NotConnected.into())
+    }
+
+    fn reconnect(&mut self) -> io::Result<()> {
+        if self
+            .retry_at
+            .is_some_and(|retry_at| Instant::now() < retry_at)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "waiting to reconnect to syslog",
+            ));
+        }
+        match connect_tcp(&self.address) {
+            Ok(stream) => {
+                self.stream = Some(stream);
+                self.backoff = MIN_RECONNECT_BACKOFF;
+                self.retry_at = None;
+                Ok(())
+            }
+            Err(error) => {
+                self.retry_at = Some(Instant::now() + self.backoff);
+                self.backoff = (self.backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                Err(error)
+            }
+        }
+    }
+}
+
+// Connect to the first reachable address, with the connect and write timeouts set
+fn connect_tcp(address: &str) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, TCP_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_write_timeout(Some(TCP_TIMEOUT))?;
+                return Ok(stream);
+            }
+            Err(error) => last_error = Some(error),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "address resolved to nothing")
+    }))
+}
+
+impl SyslogWriter {
+    fn message(&self, severity: u8, text: &[u8]) -> Vec<u8> {
+        let inner = &self.inner;
+        let mut timestamp = String::new();
+        if SystemTime
+            .format_time(&mut Writer::new(&mut timestamp))
+            .is_err()
+        {
+            timestamp = "-".to_string();
+        }
+        let pri = inner.facility.code() * 8 + severity;
+        let mut message = format!(
+            "<{pri}>1 {timestamp} {} {} {} - - ",
+            inner.hostname, inner.app_name, inner.procid
+        )
+        .into_bytes();
+        message.extend_from_slice(text.trim_ascii_end());
+        message
+    }
+
+    fn send(&self, severity: u8, text: &[u8]) -> io::Result<()> {
+        let message = self.message(severity, text);
+        self.inner
+            .connection
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .send(&message)
+    }
+}
+
+/// The writer of a single event, which sends the message when dropped.
+#[derive(Debug)]
+pub struct SyslogEventWriter<'a> {
+    writer: &'a SyslogWriter,
+    severity: u8,
+    buffer: Vec<u8>,
+}
+
+impl io::Write for SyslogEventWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SyslogEventWriter<'_> {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            // A logging sink has nowhere to report its own failures.
+            let _ = self.writer.send(self.severity, &self.buffer);
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for SyslogWriter {
+    type Writer = SyslogEventWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.writer_for_level(&Level::INFO)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        self.writer_for_level(meta.level())
+    }
+}
+
+impl SyslogWriter {
+    fn writer_for_level(&self, level: &Level) -> SyslogEventWriter<'_> {
+        SyslogEventWriter {
+            writer: self,
+            severity: syslog_severity(level),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// Make a header field printable ASCII without spaces, as RFC 5424 requires.
+fn header_field(value: &str, max_len: usize) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value
+    }
+}
+
+pub(crate) fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Read, net::TcpListener};
+
+    fn assert_message(message: &str, pri: u8, text: &str) {
+        let mut parts = message.splitn(8, ' ');
+        assert_eq!(parts.next(), Some(format!("<{pri}>1").as_str()));
+        assert!(parts.next().unwrap().ends_with('Z'));
+        assert_eq!(parts.next(), Some("test-host"));
+        assert_eq!(parts.next(), Some("my-service"));
+        assert_eq!(parts.next(), Some(std::process::id().to_string().as_str()));
+        assert_eq!(parts.next(), Some("-"));
+        assert_eq!(parts.next(), Some("-"));
+        assert_eq!(parts.next(), Some(text));
+    }
+
+    fn config(transport: SyslogTransport, address: String) -> SyslogConfig {
+        SyslogConfig {
+            hostname: Some("test-host".to_string()),
+            ..SyslogConfig::new(transport)
+                .with_address(address)
+                .with_facility(SyslogFacility::Local0)
+        }
+    }
+
+    #[test]
+    fn test_syslog_udp() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let writer = config(SyslogTransport::Udp, address)
+            .with_app_name("my-service")
+            .build_writer("ignored")
+            .unwrap();
+        writer
+            .writer_for_level(&Level::WARN)
+            .write_all(b"disk almost full\n")
+            .unwrap();
+
+        let mut buffer = [0; 1024];
+        let len = listener.recv(&mut buffer).unwrap();
+        // local0 (16) * 8 + warning (4)
+        assert_message(
+            std::str::from_utf8(&buffer[..len]).unwrap(),
+            132,
+            "disk almost full",
+        );
+    }
+
+    #[test]
+    fn test_syslog_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let writer = config(SyslogTransport::Tcp, address)
+            .with_app_name("my-service")
+            .build_writer("ignored")
+            .unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+
+        writer
+            .writer_for_level(&Level::ERROR)
+            .write_all(b"failed")
+            .unwrap();
+        drop(writer);
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+        let (len, message) = received.split_once(' ').unwrap();
+        assert_eq!(len.parse::<usize>().unwrap(), message.len());
+        // local0 (16) * 8 + err (3)
+        assert_message(message, 131, "failed");
+    }
+
+    #[test]
+    fn test_syslog_tcp_reconnect_backoff() {
+        // a port nobody listens on
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let mut connection = TcpConnection {
+            address: address.clone(),
+            stream: None,
+            backoff: MIN_RECONNECT_BACKOFF,
+            retry_at: None,
+        };
+
+        let error = connection.send(b"5 first").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+        assert_eq!(connection.backoff, MIN_RECONNECT_BACKOFF * 2);
+        // no connection is attempted until the backoff has elapsed
+        let error = connection.send(b"6 second").unwrap_err();
+        assert_eq!(error.to_string(), "waiting to reconnect to syslog");
+        assert_eq!(connection.backoff, MIN_RECONNECT_BACKOFF * 2);
+
+        let listener = TcpListener::bind(&address).unwrap();
+        connection.retry_at = Some(Instant::now());
+        connection.send(b"5 third").unwrap();
+        assert_eq!(connection.backoff, MIN_RECONNECT_BACKOFF);
+        drop(connection);
+
+        let mut received = String::new();
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.read_to_string(&mut received).unwrap();
+        assert_eq!(received, "5 third");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_syslog_unix() {
+        let path = std::env::temp_dir().join(format!("tracing-otel-syslog-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+        let writer = config(SyslogTransport::Unix, path.to_string_lossy().into_owned())
+            .build_writer("my-service")
+            .unwrap();
+        writer
+            .writer_for_level(&Level::DEBUG)
+            .write_all(b"details")
+            .unwrap();
+
+        let mut buffer = [0; 1024];
+        let len = listener.recv(&mut buffer).unwrap();
+        // local0 (16) * 8 + debug (7)
+        assert_message(std::str::from_utf8(&buffer[..len]).unwrap(), 135, "details");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_header_field() {
+        assert_eq!(header_field("my service", 48), "myservice");
+        assert_eq!(header_field("", 48), "-");
+        assert_eq!(header_field("abcdef", 3), "abc");
+    }
+}
@@ -0,0 +1,42 @@
+//! Test helpers shared by the output tests.
+
+use std::{
+    io,
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// An in-memory writer, whose clones all write to the same buffer.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+    /// The bytes written so far. Holding the lock blocks every writer.
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Vec<u8>> {
+        self.0.lock().unwrap()
+    }
+
+    /// The text written so far.
+    pub(crate) fn contents(&self) -> String {
+        String::from_utf8(self.lock().clone()).unwrap()
+    }
+}
+
+impl io::Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Buffer {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
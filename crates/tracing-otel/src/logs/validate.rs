@@ -0,0 +1,475 @@
+use crate::logs::{
+    subscriber::parse_filter, LogCompression, LogRollingRotation, Logger, LoggerFileAppender,
+    SyslogConfig, SyslogTransport,
+};
+use std::{
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+
+/// A problem with one setting of the logger configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// The path of the setting, such as `file_appenders[0].max_bytes`, or the environment
+    /// variable it was read from, such as `LOG_FORMAT`.
+    pub path: String,
+
+    /// What is wrong with the setting.
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Every problem found in a logger configuration, returned by [`Logger::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationError {
+    /// Fail with the issues, if there are any.
+    pub fn check(issues: Vec<ValidationIssue>) -> Result<(), Self> {
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { issues })
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid logger configuration:")?;
+        for issue in &self.issues {
+            write!(f, "\n  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Logger {
+    /// Check the configuration, reporting every problem found rather than the first one.
+    ///
+    /// [`Logger::init`] runs it before installing anything.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_otel_extra::Logger;
+    ///
+    /// let err = Logger::new("my-service")
+    ///     .with_sample_ratio(5.0)
+    ///     .with_metrics_interval_secs(0)
+    ///     .validate()
+    ///     .unwrap_err();
+    /// assert_eq!(err.issues.len(), 2);
+    /// assert_eq!(err.issues[0].path, "sample_ratio");
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut issues = Vec::new();
+        if self.service_name.trim().is_empty() {
+            issues.push(ValidationIssue::new("service_name", "must not be empty"));
+        }
+        let semantic = [
+            ("service_version", &self.service_version),
+            ("service_namespace", &self.service_namespace),
+            ("service_instance_id", &self.service_instance_id),
+            ("deployment_environment", &self.deployment_environment),
+        ];
+        for (path, value) in semantic {
+            if value
+                .as_deref()
+                .is_some_and(|value| value.trim().is_empty())
+            {
+                issues.push(ValidationIssue::new(path, "must not be empty"));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.sample_ratio) {
+            issues.push(ValidationIssue::new(
+                "sample_ratio",
+                format!("must be between 0.0 and 1.0, got {}", self.sample_ratio),
+            ));
+        }
+        if self.metrics_interval_secs == 0 {
+            issues.push(ValidationIssue::new(
+                "metrics_interval_secs",
+                "must be greater than zero",
+            ));
+        }
+        if self.console_buffered_lines_limit == Some(0) {
+            issues.push(ValidationIssue::new(
+                "console_buffered_lines_limit",
+                "must be greater than zero",
+            ));
+        }
+
+        let file_appenders = self
+            .file_appender
+            .iter()
+            .map(|config| ("file_appender".to_string(), config))
+            .chain(
+                self.file_appenders
+                    .iter()
+                    .enumerate()
+                    .map(|(index, config)| (format!("file_appenders[{index}]"), config)),
+            )
+            .filter(|(_, config)| config.enable)
+            .collect::<Vec<_>>();
+        for (index, (path, config)) in file_appenders.iter().enumerate() {
+            validate_file_appender(path, config, &mut issues);
+            // Two appenders writing the same files would rotate and delete each other's files
+            let (dir, prefix) = output_files(config);
+            for (other, other_config) in &file_appenders[..index] {
+                let (other_dir, other_prefix) = output_files(other_config);
+                if other_dir != dir {
+                    continue;
+                }
+                let message = if other_prefix == prefix {
+                    format!("uses the same directory and filename prefix as {other}")
+                } else if is_dot_prefix(&prefix, &other_prefix)
+                    || is_dot_prefix(&other_prefix, &prefix)
+                {
+                    format!("uses a filename prefix overlapping with {other} in the same directory")
+                } else {
+                    continue;
+                };
+                issues.push(ValidationIssue::new(path.as_str(), message));
+                break;
+            }
+        }
+
+        if let Some(config) = self.syslog.as_ref().filter(|config| config.enable) {
+            validate_syslog(config, &mut issues);
+        }
+        if let Some(config) = self.journald.as_ref().filter(|config| config.enable) {
+            validate_filter("journald.filter", config.filter.as_deref(), &mut issues);
+        }
+        ValidationError::check(issues)
+    }
+}
+
+fn validate_file_appender(
+    path: &str,
+    config: &LoggerFileAppender,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    validate_filter(&format!("{path}.filter"), config.filter.as_deref(), issues);
+    let positive = [
+        ("max_bytes", config.max_bytes),
+        ("max_total_bytes", config.max_total_bytes),
+        ("max_age_secs", config.max_age_secs),
+        (
+            "buffered_lines_limit",
+            config.buffered_lines_limit.map(|limit| limit as u64),
+        ),
+    ];
+    for (field, value) in positive {
+        if value == Some(0) {
+            issues.push(ValidationIssue::new(
+                format!("{path}.{field}"),
+                "must be greater than zero",
+            ));
+        }
+    }
+    if config.rotation == LogRollingRotation::Never
+        && config.filename_prefix_or_default().is_empty()
+        && config.filename_suffix_or_default().is_empty()
+    {
+        issues.push(ValidationIssue::new(
+            format!("{path}.filename_prefix"),
+            "a filename prefix or suffix is required when the rotation is `never`",
+        ));
+    }
+    if config.compression == LogCompression::Zstd && !cfg!(feature = "zstd") {
+        issues.push(ValidationIssue::new(
+            format!("{path}.compression"),
+            "zstd compression requires the `zstd` feature",
+        ));
+    }
+}
+
+// The directory and filename prefix of an appender, `./logs/` and `logs` being the same
+fn output_files(config: &LoggerFileAppender) -> (PathBuf, String) {
+    let dir = config.dir_or_default();
+    let dir = Path::new(&dir)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect();
+    (dir, config.filename_prefix_or_default())
+}
+
+// Whether the files of `name` can be mistaken for rotated files of `prefix`, as `app.1` with
+// `app` when there is no suffix
+fn is_dot_prefix(prefix: &str, name: &str) -> bool {
+    !prefix.is_empty()
+        && name
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+fn validate_syslog(config: &SyslogConfig, issues: &mut Vec<ValidationIssue>) {
+    validate_filter("syslog.filter", config.filter.as_deref(), issues);
+    let Some(address) = &config.address else {
+        return;
+    };
+    let valid = match config.transport {
+        SyslogTransport::Unix => !address.trim().is_empty(),
+        SyslogTransport::Udp | SyslogTransport::Tcp => address
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok()),
+    };
+    if !valid {
+        let expected = match config.transport {
+            SyslogTransport::Unix => "a socket path",
+            SyslogTransport::Udp | SyslogTransport::Tcp => "a `host:port` address",
+        };
+        issues.push(ValidationIssue::new(
+            "syslog.address",
+            format!("must be {expected}, got '{address}'"),
+        ));
+    }
+}
+
+fn validate_filter(path: &str, filter: Option<&str>, issues: &mut Vec<ValidationIssue>) {
+    if let Some(Err(err)) = filter.map(parse_filter) {
+        issues.push(ValidationIssue::new(path, format!("{err:#}")));
+    }
+}
+
+/// The field names of a struct deriving `Deserialize`.
+#[cfg(any(feature = "env", feature = "config"))]
+pub(crate) fn struct_fields<'de, T: serde::Deserialize<'de>>() -> &'static [&'static str] {
+    use serde::de::{self, Visitor};
+
+    // Record the fields passed to `deserialize_struct` and stop there
+    struct FieldsDeserializer<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> de::Deserializer<'de> for FieldsDeserializer<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("fields recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+            identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldsDeserializer(&mut fields));
+    fields
+}
+
+#[cfg(any(feature = "env", feature = "config"))]
+fn is_field<'de, T: serde::Deserialize<'de>>(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    struct_fields::<T>().contains(&name.as_str())
+}
+
+/// Whether `name`, the part of a variable after the prefix of a file appender, is one of
+/// its settings.
+#[cfg(any(feature = "env", feature = "config"))]
+pub(crate) fn is_file_appender_var(name: &str) -> bool {
+    match name.strip_prefix("JSON_") {
+        Some(name) => is_field::<crate::logs::JsonOptions>(name),
+        None => is_field::<LoggerFileAppender>(name),
+    }
+}
+
+/// Warnings about the `{prefix}*` environment variables which are not used by the logger:
+/// unknown names and settings of file appenders not listed in `{prefix}FILE_NAMES`.
+#[cfg(any(feature = "env", feature = "config"))]
+pub(crate) fn env_var_warnings(prefix: &str, vars: &[(String, String)]) -> Vec<String> {
+    use crate::logs::{JournaldConfig, JsonOptions};
+
+    let file_prefix = format!("{prefix}FILE_");
+    let names: Vec<String> = vars
+        .iter()
+        .filter(|(key, _)| key.strip_prefix(&file_prefix) == Some("NAMES"))
+        .flat_map(|(_, names)| names.split(','))
+        .map(|name| name.trim().to_uppercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+    let is_appender_var = |name: &str| {
+        name == "NAMES"
+            || is_file_appender_var(name)
+            || name.split_once('_').is_some_and(|(index, name)| {
+                index.parse::<usize>().is_ok() && is_file_appender_var(name)
+            })
+            || names.iter().any(|appender| {
+                name.strip_prefix(appender.as_str())
+                    .and_then(|name| name.strip_prefix('_'))
+                    .is_some_and(is_file_appender_var)
+            })
+    };
+
+    let mut warnings = Vec::new();
+    for (key, _) in vars {
+        let Some(name) = key.strip_prefix(prefix) else {
+            continue;
+        };
+        let known = if let Some(name) = name.strip_prefix("FILE_") {
+            if is_appender_var(name) {
+                true
+            } else if let Some((appender, _)) = name
+                .match_indices('_')
+                .map(|(index, _)| name.split_at(index))
+                .find(|(_, field)| is_file_appender_var(&field[1..]))
+            {
+                warnings.push(format!(
+                    "Unused environment variable {key}: `{appender}` is not listed in {file_prefix}NAMES"
+                ));
+                continue;
+            } else {
+                false
+            }
+        } else if let Some(name) = name.strip_prefix("JSON_") {
+            is_field::<JsonOptions>(name)
+        } else if let Some(name) = name.strip_prefix("SYSLOG_") {
+            is_field::<SyslogConfig>(name)
+        } else if let Some(name) = name.strip_prefix("JOURNALD_") {
+            is_field::<JournaldConfig>(name)
+        } else {
+            is_field::<Logger>(name)
+        };
+        if !known {
+            warnings.push(format!("Unknown environment variable {key}"));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_collects_all_issues() {
+        let logger = Logger::new("")
+            .with_sample_ratio(5.0)
+            .with_metrics_interval_secs(0)
+            .with_file_appenders(vec![
+                LoggerFileAppender::new("app").with_filter("audit=loud"),
+                LoggerFileAppender {
+                    max_bytes: Some(0),
+                    ..LoggerFileAppender::new("app")
+                },
+                LoggerFileAppender {
+                    enable: false,
+                    max_bytes: Some(0),
+                    ..LoggerFileAppender::new("disabled")
+                },
+            ])
+            .with_syslog(Some(
+                SyslogConfig::new(SyslogTransport::Udp).with_address("localhost"),
+            ));
+        let err = logger.validate().unwrap_err();
+        let paths: Vec<_> = err.issues.iter().map(|issue| issue.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "service_name",
+                "sample_ratio",
+                "metrics_interval_secs",
+                "file_appenders[0].filter",
+                "file_appenders[1].max_bytes",
+                "file_appenders[1]",
+                "syslog.address",
+            ]
+        );
+        assert_eq!(
+            err.issues[5].message,
+            "uses the same directory and filename prefix as file_appenders[0]"
+        );
+        let message = err.to_string();
+        assert!(message.starts_with("Invalid logger configuration:\n  - service_name: "));
+
+        // Rotation and retention match files by prefix, whatever their suffix
+        let logger = Logger::new("my-service").with_file_appenders(vec![
+            LoggerFileAppender::new("app").with_dir("./logs/"),
+            LoggerFileAppender {
+                filename_suffix: Some("json".to_string()),
+                ..LoggerFileAppender::new("app").with_dir("logs")
+            },
+        ]);
+        let err = logger.validate().unwrap_err();
+        assert_eq!(err.issues[0].path, "file_appenders[1]");
+
+        // Nor can a prefix extend another one with a dot in the same directory
+        let logger = Logger::new("my-service").with_file_appenders(vec![
+            LoggerFileAppender::new("app.error"),
+            LoggerFileAppender::new("app"),
+            LoggerFileAppender::new("app.error").with_dir("errors"),
+            LoggerFileAppender::new("application"),
+        ]);
+        let err = logger.validate().unwrap_err();
+        assert_eq!(err.issues.len(), 1);
+        assert_eq!(err.issues[0].path, "file_appenders[1]");
+        assert_eq!(
+            err.issues[0].message,
+            "uses a filename prefix overlapping with file_appenders[0] in the same directory"
+        );
+
+        assert!(Logger::new("my-service").validate().is_ok());
+        assert!(Logger::new("my-service")
+            .with_sample_ratio(f64::NAN)
+            .validate()
+            .is_err());
+    }
+
+    #[cfg(feature = "env")]
+    #[test]
+    fn test_env_var_warnings() {
+        let vars = [
+            ("LOG_LEVEL", "info"),
+            ("LOG_LEVL", "info"),
+            ("LOG_JSON_SPAN_LIST", "false"),
+            ("LOG_FILE_ENABLE", "true"),
+            ("LOG_FILE_JSON_FLATTEN_EVENT", "true"),
+            ("LOG_FILE_0_FILTER", "warn"),
+            ("LOG_FILE_NAMES", "audit"),
+            ("LOG_FILE_AUDIT_ENABLE", "true"),
+            ("LOG_FILE_ACCESS_LOG_ENABLE", "true"),
+            ("LOG_SYSLOG_TRANSPORT", "udp"),
+            ("LOG_SYSLOG_PORT", "514"),
+            ("OTHER_LEVEL", "info"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+        assert_eq!(
+            env_var_warnings("LOG_", &vars),
+            [
+                "Unknown environment variable LOG_LEVL",
+                "Unused environment variable LOG_FILE_ACCESS_LOG_ENABLE: `ACCESS_LOG` is not listed in LOG_FILE_NAMES",
+                "Unknown environment variable LOG_SYSLOG_PORT",
+            ]
+        );
+    }
+}
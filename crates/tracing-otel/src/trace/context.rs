@@ -200,12 +200,12 @@ mod tests {
         let span = create_span();
         println!(
             "Before set_otel_parent - span trace_id: {}",
-            span.context().span().span_context().trace_id().to_string()
+            span.context().span().span_context().trace_id()
         );
         set_otel_parent(&headers, &span);
         println!(
             "After set_otel_parent - span trace_id: {}",
-            span.context().span().span_context().trace_id().to_string()
+            span.context().span().span_context().trace_id()
         );
 
         // Verify that the trace ID from the header was used
@@ -1,14 +1,17 @@
 use anyhow::Result;
 use axum::extract::Query;
 use axum::{routing::get, Router};
-use axum_otel::{AxumOtelOnFailure, AxumOtelOnResponse, AxumOtelSpanCreator, Level};
+use axum_otel::{
+    AxumOtelOnBodyChunk, AxumOtelOnFailure, AxumOtelOnRequest, AxumOtelOnResponse,
+    AxumOtelSpanCreator, Level,
+};
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 use tracing::info;
-use tracing_otel_extra::Logger;
+use tracing_otel_extra::{GracefulShutdown, Logger};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct HelloQuery {
@@ -34,7 +37,7 @@ async fn main() -> Result<()> {
 
     let mut logger = Logger::from_env(Some("LOG_"))?;
     logger = logger.with_ansi(true);
-    let _guard = logger.init()?;
+    let shutdown = GracefulShutdown::new(logger.init()?);
 
     // Setup Axum router and server
     let app = Router::new()
@@ -45,6 +48,8 @@ async fn main() -> Result<()> {
                 .layer(
                     TraceLayer::new_for_http()
                         .make_span_with(AxumOtelSpanCreator::new().level(Level::INFO))
+                        .on_request(AxumOtelOnRequest::new())
+                        .on_body_chunk(AxumOtelOnBodyChunk::new())
                         .on_response(AxumOtelOnResponse::new().level(Level::INFO))
                         .on_failure(AxumOtelOnFailure::new().level(Level::ERROR)),
                 )
@@ -54,7 +59,10 @@ async fn main() -> Result<()> {
 
     let listener = TcpListener::bind("0.0.0.0:8080").await?;
     info!("Server is running on http://0.0.0.0:8080");
-    axum::serve(listener, app.into_make_service()).await?;
+    axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(shutdown.signal())
+        .await?;
+    shutdown.finish().await?;
 
     Ok(())
 }